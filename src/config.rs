@@ -0,0 +1,146 @@
+//! Construction of the plot configuration from library code
+
+use crate::{
+    plot::{ColorRange, PlotKind, XTickLabels},
+    theme::{Color, Theme},
+    trace::Metric,
+    Args,
+};
+use clap::Parser;
+use regex::Regex;
+use std::{num::NonZeroU32, path::PathBuf};
+
+/// Configuration of the plot
+///
+/// This is the configuration that library functions like
+/// [`load_traces()`](crate::load_traces) and [`plot::draw()`](crate::plot::draw)
+/// consume. It is the same as the command-line arguments of the `multiplot`
+/// binary, and can be built from library code with [`PlotConfig::builder()`].
+pub type PlotConfig = Args;
+
+/// Builder of a [`PlotConfig`]
+///
+/// Configuration starts from the defaults of the `multiplot` binary, which
+/// plots every benchmark from the current directory into `output.svg`, and
+/// setters override some of them. Unlike command-line parsing, the builder
+/// does not check for combinations of options that conflict with each other.
+#[derive(Clone, Debug)]
+pub struct PlotConfigBuilder(PlotConfig);
+//
+impl PlotConfigBuilder {
+    /// Path to root of Rust project where criterion data was acquired
+    pub fn input_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.0.input_path = path.into().into();
+        self
+    }
+
+    /// Path of the output image, whose extension selects its format
+    pub fn output_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.0.output_path = path.into().into();
+        self
+    }
+
+    /// Regex that selects the benchmark groups to be plotted
+    pub fn regex(mut self, regex: Regex) -> Self {
+        self.0.regex = regex;
+        self
+    }
+
+    /// Title of the plot
+    pub fn title(mut self, title: impl Into<Box<str>>) -> Self {
+        self.0.title = title.into();
+        self
+    }
+
+    /// Font family of all text in the plot
+    pub fn font(mut self, font: impl Into<Box<str>>) -> Self {
+        self.0.font = font.into();
+        self
+    }
+
+    /// Size of the output image in pixels
+    pub fn size(mut self, width: NonZeroU32, height: NonZeroU32) -> Self {
+        self.0.width = width;
+        self.0.height = height;
+        self
+    }
+
+    /// Scale factors at which the image should be rendered, see
+    /// [`PlotConfig::outputs()`]
+    pub fn resolutions(mut self, scales: impl IntoIterator<Item = NonZeroU32>) -> Self {
+        self.0.resolutions = scales.into_iter().collect();
+        self
+    }
+
+    /// Kind of plot to be drawn
+    pub fn kind(mut self, kind: PlotKind) -> Self {
+        self.0.kind = kind;
+        self
+    }
+
+    /// Forced lower bound of the vertical axis
+    pub fn min_y(mut self, min_y: f32) -> Self {
+        self.0.min_y = Some(min_y);
+        self
+    }
+
+    /// Forced upper bound of the vertical axis
+    pub fn max_y(mut self, max_y: f32) -> Self {
+        self.0.max_y = Some(max_y);
+        self
+    }
+
+    /// Label of the horizontal axis
+    pub fn x_label(mut self, x_label: impl Into<Box<str>>) -> Self {
+        self.0.x_label = x_label.into();
+        self
+    }
+
+    /// Ticks of the horizontal axis, whose logarithmic scale is either marked
+    /// at powers of ten or at each measured problem size
+    pub fn x_tick_labels(mut self, x_tick_labels: XTickLabels) -> Self {
+        self.0.x_tick_labels = x_tick_labels;
+        self
+    }
+
+    /// Metric that is plotted for each measurement, which selects the scale of
+    /// the vertical axis: logarithmic for measurements and linear for the
+    /// other metrics
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    /// Colors used when drawing the plot
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.0.theme = Some(theme);
+        self
+    }
+
+    /// Colors of the traces, which replace those of the theme
+    pub fn palette(mut self, palette: impl IntoIterator<Item = Color>) -> Self {
+        self.0.theme.get_or_insert_with(Theme::default).palette = palette.into_iter().collect();
+        self
+    }
+
+    /// Subrange of the color gradient from which trace colors are picked, when
+    /// the palette is empty
+    pub fn color_range(mut self, color_range: ColorRange) -> Self {
+        self.0.color_range = color_range;
+        self
+    }
+
+    /// Finish building the configuration
+    pub fn build(self) -> PlotConfig {
+        self.0
+    }
+}
+//
+impl Default for PlotConfigBuilder {
+    fn default() -> Self {
+        Self(
+            PlotConfig::try_parse_from(["multiplot", ""])
+                .expect("the default configuration should be valid"),
+        )
+    }
+}
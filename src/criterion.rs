@@ -2,15 +2,27 @@
 
 use crate::{Args, Result};
 use anyhow::{bail, ensure, Context};
-use serde::Deserialize;
+use clap::ValueEnum;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    path::{Component, Path},
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Display, Formatter},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
 };
 use walkdir::{DirEntry, WalkDir};
 
-/// Read raw data from Criterion
+/// Read raw data from Criterion's latest benchmark run
 pub fn read_all(args: &Args) -> Result<Vec<BenchmarkInfo>> {
+    read_dataset(args, "new")
+}
+
+/// Read raw data from a Criterion dataset
+///
+/// Criterion stores the latest benchmark results in a dataset called `new`,
+/// and saved baselines in datasets named after the baseline.
+pub fn read_dataset(args: &Args, dataset: &str) -> Result<Vec<BenchmarkInfo>> {
     // Compute criterion data path, make sure it exists
     let criterion_path = args.input_path.join("target/criterion");
     ensure!(
@@ -21,11 +33,18 @@ pub fn read_all(args: &Args) -> Result<Vec<BenchmarkInfo>> {
     // Collect data from each benchmark group in one place
     let mut benchmarks = HashMap::<_, BenchmarkInfoBuilder>::new();
 
+    // Keep track of the Criterion data formats that we encountered
+    let mut detected_formats = BTreeSet::new();
+
     // Walk the criterion path, looking for data
     for entry in WalkDir::new(&criterion_path)
         .into_iter()
-        .filter_entry(dir_entry_filter(args, &criterion_path))
-        .filter(|entry| entry.as_ref().map_or(true, |e| e.depth() >= 4))
+        .filter_entry(dir_entry_filter(args, &criterion_path, dataset))
+        .filter(|entry| {
+            entry.as_ref().map_or(true, |e| {
+                e.depth() >= 4 + args.nesting_depth && !e.file_type().is_dir()
+            })
+        })
     {
         // Check entry validity
         let entry = entry?;
@@ -44,26 +63,41 @@ pub fn read_all(args: &Args) -> Result<Vec<BenchmarkInfo>> {
             .context("Data files should have a parent directory")?;
         let benchmark_info = benchmarks.entry(parent_dir.to_path_buf()).or_default();
 
-        // Decode the JSON data
+        // Parse the JSON data and detect which Criterion version produced it
         let file_stem = relative_path
             .file_stem()
             .context("Should be a data file name")?
             .to_str()
-            .context("Data file names should be valid Unicode")?;
+            .with_context(|| {
+                format!(
+                    "Data file name {:?} should be valid Unicode",
+                    entry.file_name()
+                )
+            })?;
+        let json = serde_json::from_slice::<serde_json::Value>(&json_bytes[..])
+            .context("Failed to parse data file as JSON")?;
+        let format = DataFormat::detect(file_stem, &json);
+        if detected_formats.insert(format) && args.verbose {
+            eprintln!(
+                "Detected {format} data format in {}",
+                entry.path().display()
+            );
+        }
+
+        // Decode the JSON data
         match file_stem {
             "benchmark" => {
-                let benchmark = serde_json::from_slice::<Benchmark>(&json_bytes[..])
-                    .context("Failed to decode criterion benchmark metadata")?;
-                ensure!(
-                    args.regex.is_match(&benchmark.group_id),
-                    "Benchmark group ID should match user-specified regex if directory name does"
-                );
+                let benchmark = decode::<Benchmark>(json, format, "benchmark metadata")?;
                 benchmark_info.benchmark = Some(benchmark);
             }
             "estimates" => {
-                let estimates = serde_json::from_slice::<Estimates>(&json_bytes[..])
-                    .context("Failed to decode criterion benchmark result estimates")?;
+                let estimates = decode::<Estimates>(json, format, "benchmark result estimates")?;
                 benchmark_info.estimates = Some(estimates);
+                benchmark_info.measured_at = entry.metadata()?.modified().ok();
+            }
+            "sample" => {
+                let samples = decode::<Samples>(json, format, "benchmark samples")?;
+                benchmark_info.samples = Some(samples);
             }
             _ => bail!("No support for parsing this Criterion output yet"),
         }
@@ -75,27 +109,122 @@ pub fn read_all(args: &Args) -> Result<Vec<BenchmarkInfo>> {
         let BenchmarkInfoBuilder {
             benchmark: Some(benchmark),
             estimates: Some(estimates),
+            measured_at,
+            samples,
         } = info
         else {
             bail!("Did not get all expected data for one benchmark")
         };
-        assert_eq!(
-            guess_benchmark_name(
-                path.components()
-                    .next()
-                    .expect("Should have a benchmark directory")
-            ),
-            &*benchmark.group_id,
-            "Benchmark group directories do not follow expected naming convention"
-        );
+
+        // Directory names were only used as a hint, the group ID is the truth
+        if !args.regex.is_match(&benchmark.group_id) {
+            continue;
+        }
+
+        // Check that the benchmark lives where Criterion says it should, or
+        // without an authoritative directory name, check our naming guess
+        if let Some(directory_name) = &benchmark.directory_name {
+            let benchmark_dir = path
+                .components()
+                .skip(args.nesting_depth)
+                .collect::<PathBuf>();
+            let benchmark_dir = benchmark_dir.parent().unwrap_or(&benchmark_dir);
+            ensure!(
+                benchmark_dir == Path::new(&**directory_name),
+                "Benchmark directory {} does not match the directory name {directory_name} \
+                recorded by Criterion",
+                benchmark_dir.display()
+            );
+        } else {
+            let benchmark_group_dir = path
+                .components()
+                .nth(args.nesting_depth)
+                .with_context(|| format!("{} should have a benchmark directory", path.display()))?;
+            let guessed_name = guess_benchmark_name(benchmark_group_dir)?;
+            ensure!(
+                guessed_name == *benchmark.group_id,
+                "Benchmark group directory {:?} does not follow the expected naming convention \
+                for group {}",
+                benchmark_group_dir.as_os_str(),
+                benchmark.group_id
+            );
+        }
         result.push(BenchmarkInfo {
             benchmark,
             estimates,
+            measured_at,
+            samples,
         })
     }
     Ok(result)
 }
 
+/// Criterion JSON data format, as guessed from the fields that are present
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DataFormat {
+    /// Criterion 0.2 (capitalized estimate names, no benchmark title)
+    Criterion02,
+
+    /// Criterion 0.3 and newer, which is what multiplot expects
+    Criterion03Plus,
+
+    /// Something that does not look like any known Criterion output
+    Unknown,
+}
+//
+impl DataFormat {
+    /// Guess the data format of a Criterion data file
+    fn detect(file_stem: &str, json: &serde_json::Value) -> Self {
+        let has_field = |name: &str| json.get(name).is_some();
+        match file_stem {
+            "benchmark" if has_field("group_id") && has_field("value_str") => {
+                if has_field("title") {
+                    Self::Criterion03Plus
+                } else {
+                    Self::Criterion02
+                }
+            }
+            "estimates" if has_field("median") => Self::Criterion03Plus,
+            "estimates" if has_field("Median") => Self::Criterion02,
+            "sample" if has_field("iters") && has_field("times") => Self::Criterion03Plus,
+            _ => Self::Unknown,
+        }
+    }
+}
+//
+impl Display for DataFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Criterion02 => write!(f, "Criterion 0.2"),
+            Self::Criterion03Plus => write!(f, "Criterion 0.3+"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Decode a Criterion data file, explaining likely version mismatches
+fn decode<T: DeserializeOwned>(
+    json: serde_json::Value,
+    format: DataFormat,
+    what: &str,
+) -> Result<T> {
+    serde_json::from_value(json).with_context(|| match format {
+        DataFormat::Criterion03Plus => format!("Failed to decode criterion {what}"),
+        DataFormat::Criterion02 => format!(
+            "Failed to decode criterion {what}: this looks like data from {format}, \
+            but multiplot expects data from {}. Please re-run your benchmarks \
+            with a newer version of Criterion.",
+            DataFormat::Criterion03Plus
+        ),
+        DataFormat::Unknown => format!(
+            "Failed to decode criterion {what}: this does not look like any \
+            Criterion data format known to multiplot, which expects data from \
+            {}. Was it produced by an unsupported Criterion version?",
+            DataFormat::Criterion03Plus
+        ),
+    })
+}
+
 /// What we should eventually know about a single Criterion benchmark
 #[derive(Debug)]
 #[non_exhaustive]
@@ -105,6 +234,13 @@ pub struct BenchmarkInfo {
 
     /// Benchmark result estimates
     pub estimates: Estimates,
+
+    /// When the benchmark was measured, according to the filesystem
+    pub measured_at: Option<SystemTime>,
+
+    /// Raw measurement samples, which are only read when
+    /// `--percentile-interval` needs them
+    pub samples: Option<Samples>,
 }
 
 /// What we know about a single Criterion benchmark during file parsing
@@ -116,6 +252,12 @@ struct BenchmarkInfoBuilder {
 
     /// Benchmark result estimates
     estimates: Option<Estimates>,
+
+    /// When the benchmark was measured, according to the filesystem
+    measured_at: Option<SystemTime>,
+
+    /// Raw measurement samples
+    samples: Option<Samples>,
 }
 
 /// Criterion benchmark metadata
@@ -125,9 +267,20 @@ pub struct Benchmark {
     /// Name of the benchmark group
     pub group_id: Box<str>,
 
+    /// Name of the benchmarked function within the group, if any
+    #[serde(default)]
+    pub function_id: Option<Box<str>>,
+
     /// Value of the benchmark within the group
     pub value_str: Box<str>,
 
+    /// Path of the benchmark's output directory within Criterion's output
+    ///
+    /// This is only recorded by newer Criterion versions. When it is present,
+    /// there is no need to guess the group name from the directory name.
+    #[serde(default)]
+    pub directory_name: Option<Box<str>>,
+
     /// Throughput configuration
     //
     // TODO: Handle non-throughput (pure timing) measurements
@@ -135,6 +288,17 @@ pub struct Benchmark {
 }
 //
 impl Benchmark {
+    /// Name of the trace that this benchmark belongs to
+    ///
+    /// Benchmarks of different functions within a group may share the same
+    /// value, so they belong to different traces.
+    pub fn trace_name(&self) -> Box<str> {
+        match &self.function_id {
+            Some(function_id) => format!("{}/{function_id}", self.group_id).into(),
+            None => self.group_id.clone(),
+        }
+    }
+
     /// Decode the benchmark value as an integer
     ///
     /// Criterion allows any string in here, but I always use this field to
@@ -152,7 +316,7 @@ impl Benchmark {
 pub use criterion::Throughput;
 
 /// [`Throughput`] type information, without a value
-#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum ThroughputType {
     /// Measure throughput in terms of bytes/second. The value should be the
     /// number of bytes processed by one iteration of the benchmarked code.
@@ -172,6 +336,25 @@ pub enum ThroughputType {
     Elements,
 }
 
+//
+impl ThroughputType {
+    /// Throughput type that can be used to plot measurements of this type
+    /// alongside measurements of another type, if any
+    ///
+    /// Bytes and decimal bytes are compatible, since they only differ in how
+    /// they are displayed. Plots use decimal SI prefixes, so a mix of both is
+    /// displayed as decimal bytes.
+    pub fn common_type(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            _ if self == other => Some(self),
+            (Self::Bytes, Self::BytesDecimal) | (Self::BytesDecimal, Self::Bytes) => {
+                Some(Self::BytesDecimal)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Split the throughput type information from the inner value
 pub fn split_throughput(throughput: Throughput) -> (ThroughputType, u64) {
     match throughput {
@@ -185,8 +368,106 @@ pub fn split_throughput(throughput: Throughput) -> (ThroughputType, u64) {
 #[derive(Debug, Deserialize)]
 #[non_exhaustive]
 pub struct Estimates {
+    /// Mean execution time (ns)
+    pub mean: Estimate,
+
     /// Median execution time (ns)
     pub median: Estimate,
+
+    /// Execution time per iteration (ns), from a linear regression of the
+    /// total sample time against the number of iterations
+    ///
+    /// Criterion only computes this estimate in its linear sampling mode.
+    pub slope: Option<Estimate>,
+}
+//
+impl Estimates {
+    /// Ratio of the mean execution time to the median execution time
+    pub fn mean_median_ratio(&self) -> f32 {
+        self.mean.point_estimate / self.median.point_estimate
+    }
+
+    /// Access the estimate of a certain statistic
+    pub fn statistic(&self, statistic: Statistic) -> Result<&Estimate> {
+        match statistic {
+            Statistic::Median => Ok(&self.median),
+            Statistic::Mean => Ok(&self.mean),
+            Statistic::Slope => self.slope.as_ref().context(
+                "no slope estimate available, was this benchmark measured in flat sampling mode?",
+            ),
+        }
+    }
+}
+
+/// Statistic of a benchmark's execution times that is used as its timing
+///
+/// All of these statistics are execution times per iteration in nanoseconds,
+/// so they can all be converted into throughputs in the same way.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Statistic {
+    /// Median execution time of the samples, which is robust to outliers
+    #[default]
+    Median,
+
+    /// Mean execution time of the samples, which is sensitive to outliers
+    Mean,
+
+    /// Slope of the linear regression of sample times against iteration
+    /// counts, which is often the most stable statistic but is only available
+    /// for benchmarks measured in Criterion's linear sampling mode
+    Slope,
+}
+
+/// Confidence level that Criterion's confidence intervals are expected to have
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(into = "String")]
+pub enum ConfidenceLevel {
+    /// Estimates must have this confidence level (e.g. 0.95)
+    Fixed(f32),
+
+    /// Estimates can have any confidence level, as long as it is the same
+    /// for all plotted data points
+    Auto,
+}
+//
+impl Default for ConfidenceLevel {
+    fn default() -> Self {
+        Self::Fixed(0.95)
+    }
+}
+//
+impl Display for ConfidenceLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(level) => write!(f, "{level}"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+//
+impl FromStr for ConfidenceLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "auto" {
+            return Ok(Self::Auto);
+        }
+        let level = s
+            .parse::<f32>()
+            .context("confidence level should be a number or auto")?;
+        ensure!(
+            level > 0.0 && level < 1.0,
+            "confidence level should be between 0 and 1"
+        );
+        Ok(Self::Fixed(level))
+    }
+}
+//
+impl From<ConfidenceLevel> for String {
+    fn from(level: ConfidenceLevel) -> Self {
+        level.to_string()
+    }
 }
 
 /// Single criterion estimate
@@ -215,17 +496,114 @@ pub struct ConfidenceInterval {
     pub upper_bound: f32,
 }
 
+/// Raw measurement samples of a Criterion benchmark
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Samples {
+    /// Number of iterations of the benchmarked code in each sample
+    pub iters: Vec<f64>,
+
+    /// Total execution time of each sample (ns)
+    pub times: Vec<f64>,
+}
+//
+impl Samples {
+    /// Execution times per iteration (ns) at the bounds of a percentile
+    /// interval, interpolated linearly between the closest samples
+    pub fn interval(&self, interval: PercentileInterval) -> Result<(f32, f32)> {
+        ensure!(
+            self.iters.len() == self.times.len(),
+            "expected as many iteration counts as sample times, but got {} and {}",
+            self.iters.len(),
+            self.times.len()
+        );
+        ensure!(!self.times.is_empty(), "expected at least one sample");
+        let mut times_per_iter = self
+            .iters
+            .iter()
+            .zip(&self.times)
+            .map(|(iters, time)| time / iters)
+            .collect::<Vec<_>>();
+        times_per_iter.sort_unstable_by(f64::total_cmp);
+        let percentile = |percent: f32| {
+            let rank = f64::from(percent) / 100.0 * (times_per_iter.len() - 1) as f64;
+            let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+            let weight = rank - below as f64;
+            let time = times_per_iter[below] * (1.0 - weight) + times_per_iter[above] * weight;
+            time as f32
+        };
+        Ok((percentile(interval.lower), percentile(interval.upper)))
+    }
+}
+
+/// Percentiles of the raw samples that bound the displayed uncertainty
+///
+/// On the command line, this is specified as `<lower>,<upper>`, where both
+/// bounds are percentages between 0 and 100.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct PercentileInterval {
+    /// Percentile of the lower bound (e.g. 10)
+    pub lower: f32,
+
+    /// Percentile of the upper bound (e.g. 90)
+    pub upper: f32,
+}
+//
+impl Display for PercentileInterval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.lower, self.upper)
+    }
+}
+//
+impl FromStr for PercentileInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (lower, upper) = s
+            .split_once(',')
+            .context("percentile intervals should have the <lower>,<upper> format")?;
+        let parse_bound = |bound: &str| -> Result<f32> {
+            let value = bound
+                .trim()
+                .parse::<f32>()
+                .with_context(|| format!("invalid percentile {bound:?}"))?;
+            ensure!(
+                (0.0..=100.0).contains(&value),
+                "percentiles should be between 0 and 100, got {value}"
+            );
+            Ok(value)
+        };
+        let (lower, upper) = (parse_bound(lower)?, parse_bound(upper)?);
+        ensure!(
+            lower < upper,
+            "the lower percentile should be below the upper percentile"
+        );
+        Ok(Self { lower, upper })
+    }
+}
+
 /// DirEntry filter that only picks benchmark output and parents thereof
 fn dir_entry_filter<'res>(
     args: &'res Args,
     criterion_path: &'res Path,
+    dataset: &'res str,
 ) -> impl FnMut(&DirEntry) -> bool + 'res {
     move |entry| {
         // Discard the part of the path that we already know
         let relative_path = strip_base_path(entry, criterion_path);
 
-        // Check benchmark group directory, reject HTML report
+        // Skip extra nesting directories, reject HTML report
         let mut relative_components = relative_path.components();
+        for _ in 0..args.nesting_depth {
+            let Some(nesting_dir) = relative_components.next() else {
+                return true;
+            };
+            if nesting_dir.as_os_str() == "report" {
+                return false;
+            }
+        }
+
+        // Check benchmark group directory, reject HTML report
         let Some(benchmark_group_dir) = relative_components.next() else {
             return true;
         };
@@ -233,12 +611,22 @@ fn dir_entry_filter<'res>(
             return false;
         }
 
-        // Reverse-engineer group name from directory name
-        let benchmark_group_name = guess_benchmark_name(benchmark_group_dir);
-
-        // Check if group name matches user-specified regex
-        if !args.regex.is_match(&benchmark_group_name) {
-            return false;
+        // Reverse-engineer group name from directory name. This is only a
+        // guess, as Criterion replaces many characters with underscores, so
+        // the directory name is also accepted as a plausible group name. The
+        // authoritative group ID is checked once the metadata is read. If the
+        // directory name is not Unicode, keep the directory so that the
+        // metadata can vouch for it, or the naming check can report it.
+        if let Ok(benchmark_group_name) = guess_benchmark_name(benchmark_group_dir) {
+            // Check if group name may match user-specified regex
+            if !(args.regex.is_match(&benchmark_group_name)
+                || benchmark_group_dir
+                    .as_os_str()
+                    .to_str()
+                    .is_some_and(|dir_name| args.regex.is_match(dir_name)))
+            {
+                return false;
+            }
         }
 
         // Check input size / iteration count directory, reject HTML report
@@ -249,26 +637,35 @@ fn dir_entry_filter<'res>(
             return false;
         }
 
-        // Only accept the newest dataset
+        // Only accept the requested dataset. Benchmarks of functions within a
+        // group have an extra directory level, where the function directory
+        // precedes the input size directory.
         let Some(data_dir) = relative_components.next() else {
             return true;
         };
-        if data_dir.as_os_str() != "new" {
-            return false;
+        if data_dir.as_os_str() != dataset {
+            if data_dir.as_os_str() == "report" {
+                return false;
+            }
+            let Some(function_data_dir) = relative_components.next() else {
+                return true;
+            };
+            if function_data_dir.as_os_str() != dataset {
+                return false;
+            }
         }
 
-        // Only accept data files which we will actually use
+        // Only accept data files which we will actually use. Files whose name
+        // is not Unicode are kept, so that reading them reports an error.
         let Some(data_file) = relative_components.next() else {
             return true;
         };
-        let data_file_str = data_file
-            .as_os_str()
-            .to_str()
-            .expect("Criterion data files should have Unicode names");
-        let data_file_wo_ext = data_file_str
-            .strip_suffix(".json")
-            .expect("Criterion data files should all be JSON");
-        data_file_wo_ext == "benchmark" || data_file_wo_ext == "estimates"
+        let Some(data_file_str) = data_file.as_os_str().to_str() else {
+            return true;
+        };
+        data_file_str == "benchmark.json"
+            || data_file_str == "estimates.json"
+            || (data_file_str == "sample.json" && args.percentile_interval.is_some())
     }
 }
 
@@ -280,14 +677,14 @@ fn strip_base_path<'entry>(entry: &'entry DirEntry, criterion_path: &Path) -> &'
         .expect("Entry paths should feature the full prefix")
 }
 
-/// Guess the benchmark group name from the first path component
-fn guess_benchmark_name(benchmark_group_dir: Component<'_>) -> String {
-    let benchmark_group_dir_name = benchmark_group_dir
-        .as_os_str()
-        .to_str()
-        .expect("Benchmark directory names should be valid Unicode");
-    benchmark_group_dir_name
+/// Guess the benchmark group name from the benchmark group path component
+fn guess_benchmark_name(benchmark_group_dir: Component<'_>) -> Result<String> {
+    let benchmark_group_dir_name = benchmark_group_dir.as_os_str();
+    let benchmark_group_dir_name = benchmark_group_dir_name.to_str().with_context(|| {
+        format!("Benchmark directory name {benchmark_group_dir_name:?} should be valid Unicode")
+    })?;
+    Ok(benchmark_group_dir_name
         .chars()
         .map(|c| if c == '_' { '/' } else { c })
-        .collect()
+        .collect())
 }
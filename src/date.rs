@@ -0,0 +1,103 @@
+//! Minimal calendar date handling
+
+use crate::Result;
+use anyhow::{ensure, Context};
+use serde::Serialize;
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Format a point in time as an UTC calendar date (YYYY-MM-DD)
+pub fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs() / 86400) as i64;
+
+    // Howard Hinnant's civil_from_days algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Calendar date, specified as YYYY-MM-DD
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(into = "String")]
+pub struct Date {
+    /// Year
+    year: u16,
+
+    /// Month, from 1 to 12
+    month: u8,
+
+    /// Day of the month, from 1 to 31
+    day: u8,
+}
+//
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+//
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+//
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut components = s.trim().split('-');
+        let mut next_component = |name: &str| {
+            components
+                .next()
+                .and_then(|component| component.parse::<u32>().ok())
+                .with_context(|| {
+                    format!("date {s:?} should have the YYYY-MM-DD format, with a numerical {name}")
+                })
+        };
+        let year = next_component("year")?;
+        let month = next_component("month")?;
+        let day = next_component("day")?;
+        ensure!(
+            components.next().is_none(),
+            "date {s:?} should have the YYYY-MM-DD format"
+        );
+        ensure!(
+            (1..=12).contains(&month),
+            "date {s:?} should have a month between 1 and 12"
+        );
+        let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days_in_month = match month {
+            2 if is_leap_year => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+        ensure!(
+            (1..=days_in_month).contains(&day),
+            "date {s:?} should have a day between 1 and {days_in_month}"
+        );
+        Ok(Self {
+            year: year.try_into().context("year should fit in 16 bits")?,
+            month: month.try_into().expect("already checked to be at most 12"),
+            day: day.try_into().expect("already checked to be at most 31"),
+        })
+    }
+}
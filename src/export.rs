@@ -0,0 +1,323 @@
+//! Export of trace data to other file formats
+
+use crate::{
+    criterion::Statistic,
+    format, plot,
+    trace::{Comparison, Metric, Traces},
+    Args, Result,
+};
+use anyhow::Context;
+use plotters::style::RGBColor;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fs::File,
+    io::{self, BufWriter, Write},
+    num::NonZeroUsize,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Write the trace data to a CSV file
+///
+/// Each row is one data point, featuring both the original timing of the
+/// `--statistic` and the plotted value that was derived from it, whose columns
+/// are named after the metric and comparison. Numbers are written with full
+/// precision, unless a number of significant digits is specified.
+pub fn write_csv(args: &Args, traces: &Traces, path: &Path) -> Result<()> {
+    let file = File::create(path).context("creating the CSV file")?;
+    let mut out = BufWriter::new(file);
+    let statistic = match args.statistic {
+        Statistic::Median => "median",
+        Statistic::Mean => "mean",
+        Statistic::Slope => "slope",
+    };
+    let value = csv_value_column(traces);
+    writeln!(
+        out,
+        "trace,problem_size,{statistic}_time_ns,{value}_lower,{value},{value}_upper"
+    )?;
+    let precision = args.precision;
+    let number = |value: f32| match precision {
+        Some(_) => format::number(value, precision, 0),
+        None => value.to_string(),
+    };
+    for trace in traces.per_trace_data.iter() {
+        for (x, meas) in trace.data.iter() {
+            writeln!(
+                out,
+                "{},{x},{},{},{},{}",
+                csv_field(&trace.name),
+                number(meas.time_ns),
+                number(meas.lower_bound),
+                number(meas.point_estimate),
+                number(meas.upper_bound)
+            )?;
+        }
+    }
+    out.flush().context("writing the CSV file")
+}
+
+/// Name of the CSV columns of the plotted values
+fn csv_value_column(traces: &Traces) -> String {
+    let metric = match traces.metric {
+        Metric::Measurement if traces.throughput.is_none() => "time",
+        Metric::Measurement => "throughput",
+        Metric::Cv => "cv_percent",
+        Metric::CiWidth => "ci_width_percent",
+        Metric::MeanMedianRatio => "mean_median_ratio",
+    };
+    match traces.comparison {
+        Some(Comparison::Difference(_)) => format!("{metric}_difference"),
+        Some(Comparison::Ratio(_)) => format!("{metric}_ratio"),
+        None => metric.to_string(),
+    }
+}
+
+/// Print the score of each trace to stdout, from best to worst
+///
+/// Scores are displayed with SI prefixes and three significant digits, unless
+/// another number of significant digits is specified.
+pub fn print_scores(traces: &Traces, precision: Option<NonZeroUsize>) -> Result<()> {
+    let precision = precision.unwrap_or(NonZeroUsize::new(3).unwrap());
+    let scores = traces.scores();
+    let name_width = scores
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = io::stdout().lock();
+    for (name, score) in scores {
+        writeln!(
+            out,
+            "{name:name_width$}  {}",
+            format::si(score, Some(precision), 0)
+        )?;
+    }
+    out.flush().context("printing the trace scores")
+}
+
+/// Write the color of each legend label of split outputs to a file
+///
+/// Each line has the `<legend label>,#rrggbb` format, and labels are listed
+/// in color assignment order.
+pub fn write_legend_file(args: &Args, path: &Path) -> Result<()> {
+    let file = File::create(path).context("creating the legend file")?;
+    let mut out = BufWriter::new(file);
+    for (name, RGBColor(r, g, b)) in plot::color_key(args, []) {
+        writeln!(out, "{},#{r:02x}{g:02x}{b:02x}", csv_field(name))?;
+    }
+    out.flush().context("writing the legend file")
+}
+
+/// Write a self-contained HTML page with the plot and a table of its data
+///
+/// The plot is inlined as SVG, and the table below it has one row per data
+/// point. Clicking on a column header sorts the table by this column.
+pub fn write_html(args: &Args, traces: &Traces, path: &Path) -> Result<()> {
+    let mut svg = Vec::new();
+    plot::draw_svg_to_writer(args, traces.clone(), &mut svg).context("drawing the plot")?;
+    let svg = String::from_utf8(svg).context("decoding the SVG plot")?;
+    let file = File::create(path).context("creating the HTML file")?;
+    let mut out = BufWriter::new(file);
+
+    // Write the page header and the plot
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>{}</title>", html_text(&args.title))?;
+    writeln!(out, "<style>{HTML_STYLE}</style>\n</head>\n<body>")?;
+    writeln!(out, "{svg}")?;
+
+    // Write the data table, where numerical cells carry their exact value for
+    // sorting purposes
+    writeln!(out, "<table id=\"data\">\n<thead><tr>")?;
+    for header in [
+        "Trace",
+        "Problem size",
+        "Lower bound",
+        "Point estimate",
+        "Upper bound",
+    ] {
+        writeln!(out, "<th>{header}</th>")?;
+    }
+    writeln!(out, "</tr></thead>\n<tbody>")?;
+    let precision = args.precision.or(NonZeroUsize::new(3));
+    for trace in traces.per_trace_data.iter() {
+        for (x, meas) in trace.data.iter() {
+            write!(
+                out,
+                "<tr><td>{}</td><td data-value=\"{x}\">{x}</td>",
+                html_text(&trace.name)
+            )?;
+            for value in [meas.lower_bound, meas.point_estimate, meas.upper_bound] {
+                write!(
+                    out,
+                    "<td data-value=\"{value}\">{}</td>",
+                    format::si(value, precision, 0)
+                )?;
+            }
+            writeln!(out, "</tr>")?;
+        }
+    }
+    writeln!(out, "</tbody>\n</table>")?;
+
+    // Write the sorting script and the page footer
+    writeln!(out, "<script>{HTML_SCRIPT}</script>\n</body>\n</html>")?;
+    out.flush().context("writing the HTML file")
+}
+
+/// Style sheet of HTML exports
+const HTML_STYLE: &str = "
+svg { max-width: 100%; height: auto; }
+table { border-collapse: collapse; font-family: sans-serif; }
+th, td { padding: 0.2em 0.8em; text-align: right; }
+th { cursor: pointer; border-bottom: 1px solid; }
+th:first-child, td:first-child { text-align: left; }
+";
+
+/// Script that sorts the table of HTML exports when a header is clicked
+const HTML_SCRIPT: &str = r##"
+document.querySelectorAll("#data th").forEach((header, column) => {
+  let ascending = true;
+  header.addEventListener("click", () => {
+    const body = document.querySelector("#data tbody");
+    const key = (row) => {
+      const cell = row.cells[column];
+      return cell.dataset.value === undefined ? cell.textContent : parseFloat(cell.dataset.value);
+    };
+    const rows = Array.from(body.rows).sort((row1, row2) => {
+      const [key1, key2] = [key(row1), key(row2)];
+      return (key1 < key2 ? -1 : key1 > key2 ? 1 : 0) * (ascending ? 1 : -1);
+    });
+    ascending = !ascending;
+    body.append(...rows);
+  });
+});
+"##;
+
+/// Escape text for use within HTML
+fn html_text(text: &str) -> Cow<'_, str> {
+    if text.contains(['&', '<', '>', '"']) {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .into()
+    } else {
+        text.into()
+    }
+}
+
+/// Write a JSON manifest of the plot next to the output image
+///
+/// See [`Args::manifest`] for the manifest's location and contents. It is
+/// first written to a temporary file, then renamed into place, so that an
+/// interrupted run does not leave a half-written manifest behind.
+pub fn write_manifest(args: &Args, traces: &Traces) -> Result<()> {
+    let (x_range, y_range) = plot::line_chart_ranges(args, traces);
+    let manifest = Manifest {
+        args,
+        x_range,
+        y_range,
+        traces,
+    };
+    let path = manifest_path(args);
+    let temp_path = path.with_extension("json.tmp");
+    let file = File::create(&temp_path).context("creating the manifest file")?;
+    let mut out = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut out, &manifest).context("encoding the manifest")?;
+    out.flush().context("writing the manifest file")?;
+    std::fs::rename(&temp_path, &path).context("moving the manifest file into place")
+}
+
+/// Location of the JSON manifest of the plot
+pub fn manifest_path(args: &Args) -> PathBuf {
+    args.output_path.with_extension("json")
+}
+
+/// Contents of the JSON manifest of a plot
+#[derive(Serialize)]
+struct Manifest<'a> {
+    /// Settings that the plot was drawn with
+    args: &'a Args,
+
+    /// Horizontal axis range
+    x_range: Range<f64>,
+
+    /// Vertical axis range
+    y_range: Range<f32>,
+
+    /// Plotted trace data
+    traces: &'a Traces,
+}
+
+/// Write the point estimates as a GitHub-flavored Markdown table
+///
+/// Rows are problem sizes and columns are traces. Traces which have no data
+/// point at some problem size get a blank cell. Values have an SI prefix and
+/// three significant digits, unless another precision is specified. The table
+/// is written to stdout if `path` is `-`.
+pub fn write_markdown(traces: &Traces, path: &Path, precision: Option<NonZeroUsize>) -> Result<()> {
+    let precision = precision.unwrap_or(NonZeroUsize::new(3).unwrap());
+    if path == Path::new("-") {
+        markdown_table(traces, precision, io::stdout().lock())
+    } else {
+        let file = File::create(path).context("creating the Markdown file")?;
+        markdown_table(traces, precision, BufWriter::new(file))
+    }
+}
+
+/// Write the point estimates as a Markdown table into some writer
+fn markdown_table(traces: &Traces, precision: NonZeroUsize, mut out: impl Write) -> Result<()> {
+    // Write the header
+    write!(out, "| Problem size |")?;
+    for trace in traces.per_trace_data.iter() {
+        write!(out, " {} |", markdown_cell(&trace.name))?;
+    }
+    writeln!(out)?;
+    write!(out, "|---:|")?;
+    for _ in traces.per_trace_data.iter() {
+        write!(out, "---:|")?;
+    }
+    writeln!(out)?;
+
+    // Write one row per problem size
+    let problem_sizes = traces
+        .per_trace_data
+        .iter()
+        .flat_map(|trace| trace.data.iter().map(|(x, _)| *x))
+        .collect::<BTreeSet<_>>();
+    for x in problem_sizes {
+        write!(out, "| {x} |")?;
+        for trace in traces.per_trace_data.iter() {
+            match trace.data.binary_search_by_key(&x, |(x, _)| *x) {
+                Ok(idx) => {
+                    let value = trace.data[idx].1.point_estimate;
+                    write!(out, " {} |", format::si(value, Some(precision), 0))?
+                }
+                Err(_) => write!(out, " |")?,
+            }
+        }
+        writeln!(out)?;
+    }
+    out.flush().context("writing the Markdown table")
+}
+
+/// Escape text for use within a Markdown table cell
+fn markdown_cell(text: &str) -> Cow<'_, str> {
+    if text.contains('|') {
+        text.replace('|', "\\|").into()
+    } else {
+        text.into()
+    }
+}
+
+/// Quote a CSV field if needed
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
@@ -0,0 +1,73 @@
+//! Formatting of numbers for display
+
+use std::{borrow::Cow, num::NonZeroUsize};
+
+/// Format a number
+///
+/// If a precision is specified, the number is displayed with this many
+/// significant digits. Otherwise, it is rounded to an integer, unless some
+/// decimals are requested with a nonzero `max_decimals`, in which case useless
+/// trailing zeroes are dropped.
+pub fn number(value: f32, precision: Option<NonZeroUsize>, max_decimals: usize) -> String {
+    if let Some(precision) = precision {
+        if value == 0.0 || !value.is_finite() {
+            return format!("{value:.0$}", precision.get() - 1);
+        }
+        let magnitude = value.abs().log10().floor() as isize;
+        let decimals = precision.get() as isize - 1 - magnitude;
+        if decimals >= 0 {
+            format!("{value:.0$}", decimals as usize)
+        } else {
+            // Round away the digits before the decimal point that are not
+            // significant
+            let scale = 10.0f64.powi(-decimals as i32);
+            format!("{}", (f64::from(value) / scale).round() * scale)
+        }
+    } else {
+        let text = format!("{value:.max_decimals$}");
+        if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            text
+        }
+    }
+}
+
+/// Format a number using SI prefixes, if available
+///
+/// The precision rules are the same as for [`number()`], and apply to the
+/// number before the SI prefix.
+pub fn si(value: f32, precision: Option<NonZeroUsize>, max_decimals: usize) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return number(value, precision, max_decimals);
+    } else if value < 0.0 {
+        return format!("-{}", si(-value, precision, max_decimals));
+    }
+    let si_power = (value.log10() / 3.0).floor() as i32 * 3;
+    let si_prefix: Cow<str> = match si_power {
+        -30 => "q".into(),
+        -27 => "r".into(),
+        -24 => "y".into(),
+        -21 => "z".into(),
+        -18 => "a".into(),
+        -15 => "f".into(),
+        -12 => "p".into(),
+        -9 => "n".into(),
+        -6 => "µ".into(),
+        -3 => "m".into(),
+        0 => "".into(),
+        3 => "k".into(),
+        6 => "M".into(),
+        9 => "G".into(),
+        12 => "T".into(),
+        15 => "P".into(),
+        18 => "E".into(),
+        21 => "Z".into(),
+        24 => "Y".into(),
+        27 => "R".into(),
+        30 => "Q".into(),
+        other => format!(".10^{other}").into(),
+    };
+    let base = value / 10.0f32.powi(si_power);
+    format!("{}{si_prefix}", number(base, precision, max_decimals))
+}
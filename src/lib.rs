@@ -0,0 +1,1100 @@
+//! Bulk plotting of criterion benchmark data
+//!
+//! The `multiplot` binary is a thin CLI wrapper around this library, which can
+//! also be used to embed criterion plots into other applications.
+
+pub mod config;
+pub mod criterion;
+pub mod date;
+pub mod export;
+mod format;
+pub mod model;
+mod palette;
+pub mod plot;
+pub mod theme;
+pub mod trace;
+
+use crate::{
+    config::PlotConfigBuilder,
+    criterion::{ConfidenceLevel, PercentileInterval, Statistic, ThroughputType},
+    date::Date,
+    model::Model,
+    plot::{
+        ColorRange, ErrorStyle, Gridlines, LegendOutside, LineSource, PlotKind, Spines, TargetBand,
+        Tier, XTickLabels,
+    },
+    theme::Theme,
+    trace::{Comparison, DedupX, Metric, Snapshot, TraceLimitStrategy, Traces, Transform},
+};
+use anyhow::{bail, ensure, Context};
+use clap::Parser;
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    num::{NonZeroU32, NonZeroUsize},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Maximal number of traces in `--preview` mode
+const PREVIEW_MAX_TRACES: usize = 10;
+
+/// Simple bulk plotter from criterion data
+#[derive(Clone, Debug, Parser, Serialize)]
+#[command(version, about)]
+pub struct Args {
+    /// Path to root of Rust project where criterion data was acquired
+    #[arg(short, long, default_value = ".")]
+    pub input_path: Box<Path>,
+
+    /// Number of extra directory levels above benchmark group directories
+    ///
+    /// Some Criterion setups nest results under additional directories (e.g.
+    /// one per measurement name) between `target/criterion` and the benchmark
+    /// group directories. Set this to the number of such levels.
+    #[arg(long, default_value = "0")]
+    pub nesting_depth: usize,
+
+    /// Name of output image
+    #[arg(short, long, default_value = "./output.svg")]
+    pub output_path: Box<Path>,
+
+    /// Template of the name of the output image, overriding `--output-path`
+    ///
+    /// The template may contain the `{regex}`, `{throughput}` (`bytes`,
+    /// `elements` or `time`), `{date}` (date of the newest measurement, as
+    /// YYYY-MM-DD) and `{ext}` (extension of `--output-path`) placeholders.
+    /// Characters of the expanded values which are not safe in file names are
+    /// replaced with `_`, e.g. `--output-template "{regex}-{date}.{ext}"`.
+    #[arg(long, value_name = "TEMPLATE", default_value = None)]
+    pub output_template: Option<Box<str>>,
+
+    /// Write one plot per group of traces instead of a single plot
+    ///
+    /// A trace's group is the part of its name before the first `/`, and the
+    /// rest of its name is used as its legend label. Each group is plotted
+    /// into a file named after the output path with `-<group>` appended to
+    /// the file stem. Colors are assigned to legend labels across all groups,
+    /// so that a given label has the same color in every file.
+    #[arg(long, conflicts_with = "also_regex")]
+    pub split_output: bool,
+
+    /// Write the colors of the traces of all split outputs to this file
+    ///
+    /// Each line of the file has the `<legend label>,#rrggbb` format, which
+    /// documents the colors that are shared by all split outputs when their
+    /// individual legends are omitted with `--no-legend`.
+    #[arg(long, value_name = "PATH", default_value = None, requires = "split_output")]
+    pub legend_file: Option<Box<Path>>,
+
+    /// Criterion baseline to be overlaid as dashed traces
+    ///
+    /// This can be `base` (the benchmark run before the latest one), or the
+    /// name of a baseline that was saved using Criterion's `--save-baseline`.
+    #[arg(short, long, default_value = None)]
+    pub baseline: Option<Box<str>>,
+
+    /// Label of baseline traces in the legend
+    ///
+    /// Baseline traces are named after the matching trace, followed by this
+    /// label in parentheses. Defaults to the name of the baseline.
+    #[arg(long, default_value = None)]
+    pub baseline_label: Option<Box<str>>,
+
+    /// Fail if any trace regressed by more than this percentage with respect
+    /// to the baseline, at any problem size
+    ///
+    /// The plot is still drawn, then the offending traces are listed and
+    /// multiplot exits with a nonzero status. This is meant to be used as a
+    /// performance regression gate in continuous integration.
+    ///
+    /// Regressions are checked on the measurements of a single chart, so this
+    /// cannot be combined with chart grids or with options that alter, drop
+    /// or add data points and traces.
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        requires = "baseline",
+        conflicts_with_all = [
+            "also_regex",
+            "panels",
+            "transform",
+            "smooth_window",
+            "max_error_factor",
+            "drop_flat",
+            "hide",
+            "max_traces",
+            "preview",
+            "interpolate_missing",
+            "diff_to",
+            "relative_to",
+            "relative_to_first",
+        ]
+    )]
+    pub fail_on_regression: Option<f32>,
+
+    /// Also export the plotted data points to this CSV file
+    #[arg(long, default_value = None)]
+    pub csv_output: Option<Box<Path>>,
+
+    /// Also print the plotted point estimates as a Markdown table to this
+    /// file, or to stdout if the path is `-`
+    ///
+    /// The table has one row per problem size and one column per trace, which
+    /// is convenient for pasting into pull request descriptions.
+    #[arg(long, default_value = None)]
+    pub markdown: Option<Box<Path>>,
+
+    /// Also write a self-contained HTML page with the plot and a sortable
+    /// table of the plotted data to this file
+    #[arg(long, default_value = None)]
+    pub html_output: Option<Box<Path>>,
+
+    /// Also write a JSON manifest next to the output image
+    ///
+    /// The manifest has the same name as the output image, with a `.json`
+    /// extension. It records the settings that the plot was drawn with, the
+    /// plotted trace data and the axis ranges, which is enough to archive
+    /// the plot along with its provenance.
+    #[arg(long)]
+    pub manifest: bool,
+
+    /// Print a score for each trace to stdout, from best to worst
+    ///
+    /// The score of a trace is the geometric mean of its point estimates
+    /// across all problem sizes, which summarizes its performance as a single
+    /// number for quick rankings. The plot is still drawn.
+    #[arg(long)]
+    pub score: bool,
+
+    /// Number of significant digits of displayed numbers
+    ///
+    /// This applies to axis labels and to the values of CSV and Markdown
+    /// exports. By default, axis labels have no decimals except on linear and
+    /// ratio axes, Markdown tables have three significant digits and CSV files
+    /// have full precision.
+    #[arg(long, default_value = None)]
+    pub precision: Option<NonZeroUsize>,
+
+    /// Width of the output image in pixels
+    #[arg(short = 'W', long, default_value = "1920")]
+    pub width: NonZeroU32,
+
+    /// Height of the output image in pixels
+    #[arg(short = 'H', long, default_value = "1080")]
+    pub height: NonZeroU32,
+
+    /// Comma-separated scale factors at which the image should be rendered
+    ///
+    /// For each scale factor k, an image that is k times wider and taller is
+    /// written next to the output path, with an @kx suffix (e.g. output.png
+    /// becomes output@1x.png, output@2x.png...). Text and markers scale with
+    /// the image size.
+    #[arg(long, value_delimiter = ',')]
+    pub resolutions: Vec<NonZeroU32>,
+
+    /// Reduce PNG output to an indexed palette of 2^N colors
+    ///
+    /// Plots feature few colors, so this makes PNG files much smaller at
+    /// little cost in image quality. A depth of 4 to 6 bits is usually enough
+    /// for plots with a handful of traces.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub palette_depth: Option<u8>,
+
+    /// Render bitmap outputs without antialiasing
+    ///
+    /// Every pixel then either gets the color of the shape or text that
+    /// covers it, or keeps the color below it, so the output does not depend
+    /// on the subtleties of antialiasing. This makes bitmap outputs suitable
+    /// for pixel-exact comparisons, and more compact. SVG outputs are not
+    /// affected.
+    #[arg(long)]
+    pub no_antialias: bool,
+
+    /// Title of the plot
+    #[arg(short, long, default_value = "Benchmark results")]
+    pub title: Box<str>,
+
+    /// Font family of all text in the plot
+    ///
+    /// In SVG outputs, text is kept as text elements that reference this font
+    /// family, so that it can be selected and restyled in SVG editors.
+    #[arg(long, value_name = "FAMILY", default_value = "sans-serif")]
+    pub font: Box<str>,
+
+    /// Forced lower bound of the Y axis
+    ///
+    /// Will automatically set the Y scale to fit all traces by default
+    #[arg(short = 'y', long, default_value = None)]
+    pub min_y: Option<f32>,
+
+    /// Forced upper bound of the Y axis
+    ///
+    /// Will automatically set the Y scale to fit all traces by default
+    #[arg(short = 'Y', long, default_value = None)]
+    pub max_y: Option<f32>,
+
+    /// Unit of element-based throughput measurement
+    ///
+    /// This will be used, along with an SI prefix and a "per second" suffix
+    /// (see `--throughput-suffix`), to label the plot's vertical axis in the
+    /// presence of such measurements.
+    #[arg(short, long, default_value = "FLOP")]
+    pub element_throughput_unit: Box<str>,
+
+    /// Suffix appended to the throughput unit on the vertical axis
+    ///
+    /// This is used verbatim, so any spacing must be included. For example,
+    /// with an element throughput unit of "FLOP", a suffix of "s" gives
+    /// "FLOPs" and a suffix of " per second" gives "FLOP per second".
+    #[arg(long, default_value = "/s")]
+    pub throughput_suffix: Box<str>,
+
+    /// Label of the horizontal axis
+    ///
+    /// Depending on the project, this can be an input size or an iteration
+    /// count, so we need full control over labeling there. If this is `auto`,
+    /// the label is inferred from the throughput type of the benchmarks
+    /// (e.g. "Bytes" for byte throughput).
+    #[arg(short, long, default_value = "Input size (f32s)")]
+    pub x_label: Box<str>,
+
+    /// Unit of the problem sizes, appended to the horizontal axis label
+    ///
+    /// The unit is displayed in brackets after the label, e.g. "Input size
+    /// [bytes]". If this is `auto`, the unit is inferred from the throughput
+    /// type of the benchmarks. By default, the label is displayed as is.
+    #[arg(long, value_name = "UNIT", default_value = None)]
+    pub x_unit: Option<Box<str>>,
+
+    /// Labels of the ticks of the horizontal axis
+    ///
+    /// With `value-str`, there is one tick per measured problem size, labeled
+    /// with the parameter string that was given to Criterion.
+    #[arg(long, value_enum, default_value_t = XTickLabels::Powers)]
+    pub x_tick_labels: XTickLabels,
+
+    /// Kind of plot to be drawn
+    #[arg(short, long, value_enum, default_value_t = PlotKind::Line)]
+    pub kind: PlotKind,
+
+    /// Part of each measurement that the lines of line charts go through
+    ///
+    /// Error bars still display the whole confidence interval. For throughput
+    /// measurements, `lower` is the worst case and `upper` the best case, and
+    /// it is the other way around for execution times.
+    #[arg(long, value_enum, default_value_t = LineSource::Point)]
+    pub line_source: LineSource,
+
+    /// How the confidence intervals of line charts are displayed
+    #[arg(long, value_enum, default_value_t = ErrorStyle::Bars)]
+    pub error_style: ErrorStyle,
+
+    /// Borders of the chart that are drawn
+    ///
+    /// With `lr`, only the left and bottom axes are drawn, for a minimalist
+    /// look. With `box`, the chart is framed on all four sides.
+    #[arg(long, value_enum, default_value_t = Spines::Lr)]
+    pub spines: Spines,
+
+    /// Whether the gridlines of line charts are drawn behind or in front of
+    /// the traces
+    ///
+    /// Drawing them in front keeps the gridlines readable on busy plots.
+    #[arg(long, value_enum, default_value_t = Gridlines::Behind)]
+    pub gridlines: Gridlines,
+
+    /// Spread the markers and error bars of traces horizontally
+    ///
+    /// When several traces are measured at the same problem sizes, their
+    /// markers and error bars hide each other. With this option, they are
+    /// offset side by side, spreading over this fraction of the width of the
+    /// horizontal axis (e.g. 0.01), while lines still go through the true
+    /// problem sizes.
+    #[arg(long, value_name = "FRACTION", value_parser = parse_jitter)]
+    pub jitter: Option<f64>,
+
+    /// Draw the legend outside of the chart, on the specified side
+    ///
+    /// By default, the legend is drawn inside of the chart, where it may
+    /// cover some data.
+    #[arg(long, value_enum, default_value = None, conflicts_with = "no_legend")]
+    pub legend_outside: Option<LegendOutside>,
+
+    /// Do not draw the legend of line charts
+    ///
+    /// This is useful when the trace colors are documented elsewhere, e.g. in
+    /// the `--legend-file` of split outputs.
+    #[arg(long)]
+    pub no_legend: bool,
+
+    /// Label each trace of line charts next to its last data point
+    ///
+    /// Labels are drawn in the color of their trace, on the right of the
+    /// chart, and nudged vertically so that they do not overlap. Combine this
+    /// with `--no-legend` to replace the legend with these labels.
+    #[arg(long)]
+    pub label_lines: bool,
+
+    /// Annotate the plot with statistics about the benchmark data
+    ///
+    /// This draws a small footnote with the number of benchmarks and data
+    /// points, the dates at which measurements were taken, and the date at
+    /// which the plot was generated.
+    #[arg(long)]
+    pub show_stats: bool,
+
+    /// Draw the width of confidence intervals in a track below line charts
+    ///
+    /// For each problem size, the track shows the distance between the upper
+    /// and lower bound of each measurement, as a percentage of its point
+    /// estimate.
+    #[arg(long, conflicts_with = "diff_to")]
+    pub ci_track: bool,
+
+    /// Seed used to shuffle the assignment of colors to traces
+    ///
+    /// By default, traces are colored in order along the color gradient. If
+    /// neighboring traces are hard to tell apart, a seed can be specified to
+    /// shuffle colors instead. For a given seed and set of traces, the colors
+    /// are guaranteed to be identical on every platform.
+    #[arg(long, default_value = None)]
+    pub color_seed: Option<u64>,
+
+    /// Regex used to group traces into color families
+    ///
+    /// Traces whose name yield the same text for the first capture group of
+    /// this regex (or for the whole regex, if it has no capture group) are
+    /// drawn in shades of the same color. Traces which do not match the regex
+    /// get a color of their own.
+    #[arg(long, value_name = "REGEX", default_value = None)]
+    #[serde(serialize_with = "serialize_optional_regex")]
+    pub color_by: Option<Regex>,
+
+    /// Subrange of the color gradient from which trace colors are picked
+    ///
+    /// Specified as `<start>,<end>`, with bounds between 0 and 1. Narrowing
+    /// the range avoids the hues at the ends of the gradient, which are
+    /// hard to tell apart. Ignored if the theme specifies a palette.
+    #[arg(long, value_name = "START,END", default_value = "0,1")]
+    pub color_range: ColorRange,
+
+    /// Performance tier drawn as a faint horizontal band behind the traces
+    ///
+    /// Tiers are specified as `<value>=<color>`, e.g. `1e9=#00ff00`. Each
+    /// tier's band starts at its value on the vertical axis and extends up to
+    /// the start of the next tier, or to the top of the chart. This option can
+    /// be specified multiple times.
+    #[arg(long, value_name = "VALUE=COLOR")]
+    pub tier: Vec<Tier>,
+
+    /// Target performance window, shaded in a faint horizontal band behind
+    /// the traces (e.g. 8e9,10e9)
+    ///
+    /// The window is specified as `<lower>,<upper>` bounds on the vertical
+    /// axis, which makes it obvious where traces enter or leave it. Its color
+    /// is the `target_region` of the theme.
+    #[arg(long, value_name = "LOWER,UPPER")]
+    pub target_band: Option<TargetBand>,
+
+    /// Theoretical model of the measurements, drawn as a reference curve
+    ///
+    /// The model is an expression of the problem size `n`, e.g. `2e9*n/(n+64)`,
+    /// which may use the `+`, `-`, `*`, `/` and `^` operators, parentheses and
+    /// the `sqrt`, `exp`, `ln`, `log2` and `log10` functions. It is evaluated
+    /// across the horizontal range of the plot, and its value is expressed in
+    /// the unit of the vertical axis.
+    #[arg(long, value_name = "EXPR")]
+    pub model: Option<Model>,
+
+    /// JSON file defining the colors used in the plot
+    ///
+    /// The file contains an object whose optional keys are `background`,
+    /// `text`, `axis`, `bold_mesh`, `light_mesh`, `legend_background`,
+    /// `legend_border`, `reference_line`, `slower_region`, `target_region`,
+    /// `faster_cell` and `slower_cell`, each associated with a `#rrggbb` or
+    /// `#rrggbbaa` color string, and `palette`, a list of trace colors.
+    /// Unspecified colors keep their default value.
+    ///
+    /// Theme files use JSON rather than TOML because JSON is already needed to
+    /// read Criterion's output, so no extra parser dependency is required.
+    #[arg(long = "theme-file", value_name = "PATH", value_parser = Theme::parse_arg)]
+    pub theme: Option<Theme>,
+
+    /// Regex with a capture group, used to merge traces into a single one
+    ///
+    /// Traces whose name matches this regex are grouped according to the
+    /// text captured by the first capture group, and each group of traces is
+    /// merged into a single trace named after the captured text. At each
+    /// problem size, merged measurements use the geometric mean of the point
+    /// estimates and the envelope of the confidence intervals.
+    #[arg(long, default_value = None)]
+    #[serde(serialize_with = "serialize_optional_regex")]
+    pub merge: Option<Regex>,
+
+    /// Combine the data points of a trace that share a problem size
+    ///
+    /// By default, it is an error for a trace to have several data points at
+    /// the same problem size, as happens when a benchmark was renamed from
+    /// `016` to `16`. With this option, one of the colliding measurements is
+    /// kept instead, and `--verbose` reports how much they disagreed.
+    #[arg(long, value_enum, default_value = None)]
+    pub dedup_x: Option<DedupX>,
+
+    /// Statistic of the execution times that is plotted
+    #[arg(
+        long,
+        visible_alias = "center",
+        value_enum,
+        default_value_t = Statistic::Median
+    )]
+    pub statistic: Statistic,
+
+    /// Statistic whose confidence interval is plotted, if not `--statistic`
+    ///
+    /// For example, `--center mean --band median` plots the mean execution
+    /// time with the confidence interval of the median, which is more robust
+    /// to outliers. Since the interval then comes from another estimate, it
+    /// may not contain the plotted value, which is reported as a warning.
+    #[arg(long, value_enum, default_value = None)]
+    pub band: Option<Statistic>,
+
+    /// Confidence level that Criterion's confidence intervals must have
+    ///
+    /// This should match the confidence level that Criterion was configured
+    /// with. With `auto`, any confidence level is accepted, as long as all
+    /// plotted data points share it.
+    #[arg(long, value_name = "LEVEL", default_value_t = ConfidenceLevel::default())]
+    pub confidence_level: ConfidenceLevel,
+
+    /// Percentiles of the raw samples that bound the displayed uncertainty,
+    /// instead of Criterion's confidence interval (e.g. 10,90)
+    ///
+    /// The bounds are computed from the execution times per iteration of the
+    /// samples that Criterion recorded, so they show the spread of the
+    /// measurements rather than the uncertainty of the statistic.
+    #[arg(long, value_name = "LOWER,UPPER", conflicts_with = "band")]
+    pub percentile_interval: Option<PercentileInterval>,
+
+    /// Transform applied to the data of each trace before plotting
+    #[arg(long, value_enum, default_value_t = Transform::None)]
+    pub transform: Transform,
+
+    /// Smooth each trace with a rolling geometric mean over this many points
+    ///
+    /// Each measurement is replaced by the geometric mean of the measurements
+    /// in a window of problem sizes centered on it, which shrinks near the
+    /// ends of the trace. This clarifies the trend of noisy benchmarks.
+    #[arg(long, default_value = None, conflicts_with = "transform")]
+    pub smooth_window: Option<NonZeroUsize>,
+
+    /// Name of a trace that every trace should be compared with
+    ///
+    /// When this is set, the plot displays the signed difference between each
+    /// trace's measurements and those of the reference trace at the same
+    /// problem size, on a linear vertical axis. Problem sizes where the
+    /// reference trace has no data are not plotted.
+    #[arg(long, default_value = None)]
+    pub diff_to: Option<Box<str>>,
+
+    /// Name of a trace that every trace should be normalized by
+    ///
+    /// When this is set, the plot displays the ratio between each trace's
+    /// measurements and those of the reference trace at the same problem
+    /// size. Problem sizes where the reference trace has no data are not
+    /// plotted.
+    #[arg(long, default_value = None, conflicts_with = "diff_to")]
+    pub relative_to: Option<Box<str>>,
+
+    /// Normalize every trace by the first plotted trace
+    ///
+    /// This is a shorthand for `--relative-to` with the name of the first
+    /// trace in legend order.
+    #[arg(long, conflicts_with_all = ["diff_to", "relative_to"])]
+    pub relative_to_first: bool,
+
+    /// Interpolate missing data points, so that traces measured at different
+    /// problem sizes can be compared
+    ///
+    /// Each trace gets an estimated data point at every problem size where
+    /// another trace was measured, as long as it lies between two of its own
+    /// measurements. This is done by linear interpolation in log-log space,
+    /// so these data points are not measurements, but they let
+    /// `--relative-to` and `--diff-to` compare traces at every problem size.
+    #[arg(long)]
+    pub interpolate_missing: bool,
+
+    /// Metric that is plotted for each measurement
+    ///
+    /// With `cv`, the coefficient of variation of each measurement (standard
+    /// error divided by point estimate) is plotted on a linear percentage
+    /// axis, which highlights the problem sizes with unstable measurements.
+    /// With `ci-width`, the relative width of the confidence interval of each
+    /// measurement is plotted in the same way. With `mean-median-ratio`, the
+    /// ratio of the mean execution time to the median execution time is
+    /// plotted, which reveals skewed timing distributions and the influence
+    /// of outliers.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Metric::Measurement,
+        conflicts_with_all = ["diff_to", "relative_to", "relative_to_first", "fail_on_regression"]
+    )]
+    pub metric: Metric,
+
+    /// Comma-separated metrics to be plotted in stacked charts
+    ///
+    /// For example, `--panels throughput,ci-width` draws the measurements on
+    /// top and the width of their confidence intervals below, sharing the
+    /// horizontal axis. See `--metric` for the available metrics.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["metric", "diff_to", "relative_to", "relative_to_first"]
+    )]
+    pub panels: Vec<Metric>,
+
+    /// Maximal ratio between error bar endpoints and the point estimate
+    ///
+    /// Error bars which extend further than this factor above or below the
+    /// point estimate are truncated, which keeps a few very noisy measurements
+    /// from squashing all other data on the vertical axis.
+    #[arg(long, default_value = None)]
+    pub max_error_factor: Option<f32>,
+
+    /// Do not plot traces whose measurements do not vary with problem size
+    ///
+    /// This declutters plots that focus on scaling behavior, by removing
+    /// traces whose point estimates have a relative standard deviation below
+    /// the `--flatness-threshold`.
+    #[arg(long)]
+    pub drop_flat: bool,
+
+    /// Relative standard deviation below which `--drop-flat` removes a trace
+    #[arg(long, default_value = "0.01")]
+    pub flatness_threshold: f32,
+
+    /// Comma-separated names of two traces whose crossover should be marked
+    ///
+    /// Problem sizes where the point estimates of these traces cross are
+    /// marked by a labeled vertical line, interpolating between data points.
+    #[arg(long, value_delimiter = ',', value_name = "A,B")]
+    pub crossover: Vec<Box<str>>,
+
+    /// Comma-separated names of traces that should not be plotted
+    ///
+    /// Unlike the regex, this matches the final trace names, after merging
+    /// and other post-processing, which is convenient for fine-tuning the
+    /// final plot. Hidden traces can still be used as a comparison reference.
+    #[arg(long, value_delimiter = ',')]
+    pub hide: Vec<Box<str>>,
+
+    /// Maximal number of traces to be plotted
+    ///
+    /// If more traces match the regex, some of them are discarded according
+    /// to the `--trace-limit-strategy`.
+    #[arg(long, default_value = None)]
+    pub max_traces: Option<NonZeroUsize>,
+
+    /// Strategy used to pick traces when there are more than `--max-traces`
+    #[arg(long, value_enum, default_value_t = TraceLimitStrategy::Top)]
+    pub trace_limit_strategy: TraceLimitStrategy,
+
+    /// Problem size at which single-size plots are drawn
+    ///
+    /// Plots which only display one data point per trace, like caterpillar
+    /// plots, use the data point at this problem size. If unset, each trace
+    /// must have a single data point.
+    #[arg(short, long, default_value = None)]
+    pub problem_size: Option<usize>,
+
+    /// Criterion data snapshot whose evolution should be tracked
+    ///
+    /// Snapshots are specified as `<label>=<path>`, where the path is the root
+    /// of a Rust project where Criterion data was acquired, like
+    /// `--input-path`, and the label identifies the snapshot, e.g. the commit
+    /// or date of the measurements. When this option is specified multiple
+    /// times, the input path is ignored and the snapshots become the
+    /// horizontal axis, in the order they were specified. Each trace then
+    /// shows how the measurement at `--problem-size` evolved over time, and
+    /// `--x-label auto` labels the horizontal axis "Snapshot".
+    #[arg(
+        long,
+        value_name = "LABEL=PATH",
+        requires = "problem_size",
+        conflicts_with = "baseline"
+    )]
+    pub track: Vec<Snapshot>,
+
+    /// Only track the snapshots that were measured on or after this date
+    /// (YYYY-MM-DD)
+    ///
+    /// The labels of the tracked snapshots must then be dates in the same
+    /// format.
+    #[arg(long, value_name = "DATE", requires = "track")]
+    pub since: Option<Date>,
+
+    /// Only track the snapshots that were measured on or before this date
+    /// (YYYY-MM-DD)
+    ///
+    /// The labels of the tracked snapshots must then be dates in the same
+    /// format.
+    #[arg(long, value_name = "DATE", requires = "track")]
+    pub until: Option<Date>,
+
+    /// Treat data quality warnings as errors
+    #[arg(short, long)]
+    pub strict: bool,
+
+    /// Only warn when the plot cannot be written, instead of failing
+    ///
+    /// This is an escape hatch for targets that report spurious errors once
+    /// the plot has been written, like some network filesystems.
+    #[arg(long)]
+    pub no_present_check: bool,
+
+    /// Skip checking that the data points of each trace are sorted
+    ///
+    /// Data points are sorted by problem size after loading them, which is a
+    /// safety net in case a data source yields them out of order. This option
+    /// skips this step when the data is known to be sorted.
+    #[arg(long)]
+    pub assume_sorted: bool,
+
+    /// Print additional diagnostics on stderr
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Quickly render a low-fidelity preview of the plot
+    ///
+    /// This is meant for checking the trace selection and plot layout before
+    /// rendering the final image. The plot is written as SVG (the output path
+    /// gets a `.svg` extension), without error bars or markers, and at most
+    /// 10 traces are plotted, unless `--max-traces` is lower.
+    #[arg(long)]
+    pub preview: bool,
+
+    /// Additional regex matching traces to be plotted in a separate chart
+    ///
+    /// When this option is specified one or more times, the output image is a
+    /// grid of charts, one per regex, each titled after its regex.
+    #[arg(short, long, default_value = None)]
+    #[serde(serialize_with = "serialize_regexes")]
+    pub also_regex: Vec<Regex>,
+
+    /// Regex matching the traces to be plotted
+    #[serde(serialize_with = "serialize_regex")]
+    pub regex: Regex,
+
+    /// Trace names that colors are assigned to, in addition to the plotted
+    /// traces
+    ///
+    /// This is used to give traces consistent colors across several plots,
+    /// like those of `--split-output`. Names are listed in color order, and
+    /// plotted traces which are not listed come after them.
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub color_names: Vec<Box<str>>,
+}
+//
+impl Args {
+    /// Start building a configuration from library code, with the same
+    /// defaults as the command-line interface
+    pub fn builder() -> PlotConfigBuilder {
+        PlotConfigBuilder::default()
+    }
+
+    /// Plot size in plotters's expected format
+    pub fn plot_size(&self) -> (u32, u32) {
+        (self.width.get(), self.height.get())
+    }
+
+    /// Output files to be written and the matching plot size
+    ///
+    /// This is just the output path and plot size, unless multiple
+    /// `--resolutions` were requested. In `--preview` mode, images are always
+    /// written as SVG.
+    pub fn outputs(&self) -> Vec<(PathBuf, (u32, u32))> {
+        let output_path = if self.preview {
+            self.output_path.with_extension("svg")
+        } else {
+            self.output_path.to_path_buf()
+        };
+        if self.resolutions.is_empty() {
+            return vec![(output_path, self.plot_size())];
+        }
+        let (width, height) = self.plot_size();
+        let stem = output_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        self.resolutions
+            .iter()
+            .map(|scale| {
+                let mut file_name = format!("{stem}@{scale}x");
+                if let Some(extension) = output_path.extension() {
+                    file_name.push('.');
+                    file_name.push_str(&extension.to_string_lossy());
+                }
+                let size = (width * scale.get(), height * scale.get());
+                (output_path.with_file_name(file_name), size)
+            })
+            .collect()
+    }
+
+    /// Maximal number of traces to be plotted, if any
+    ///
+    /// This is `--max-traces`, capped in `--preview` mode.
+    pub fn max_traces(&self) -> Option<usize> {
+        let max_traces = self.max_traces.map(NonZeroUsize::get);
+        if self.preview {
+            Some(max_traces.map_or(PREVIEW_MAX_TRACES, |max| max.min(PREVIEW_MAX_TRACES)))
+        } else {
+            max_traces
+        }
+    }
+
+    /// Kind of plot to be drawn
+    ///
+    /// This is `--kind`, except that `--preview` mode draws scatter plots with
+    /// sized markers as plain line charts.
+    pub fn kind(&self) -> PlotKind {
+        if self.preview && self.kind == PlotKind::ScatterSized {
+            PlotKind::Line
+        } else {
+            self.kind
+        }
+    }
+
+    /// Configuration of each chart in a grid of charts
+    ///
+    /// There is one chart per regex. It is titled after the regex, and
+    /// otherwise uses the same configuration as the whole plot.
+    pub fn panel_args(&self) -> impl Iterator<Item = Self> + '_ {
+        std::iter::once(&self.regex)
+            .chain(&self.also_regex)
+            .map(|regex| Self {
+                title: regex.as_str().into(),
+                regex: regex.clone(),
+                also_regex: Vec::new(),
+                ..self.clone()
+            })
+    }
+
+    /// Configuration where the output path is expanded from
+    /// `--output-template`, if any, for plotting some traces
+    pub fn expand_output_template(&self, traces: &Traces) -> Result<Self> {
+        let Some(template) = &self.output_template else {
+            return Ok(self.clone());
+        };
+        let mut output_path = String::new();
+        let mut rest = &**template;
+        while let Some((literal, after_brace)) = rest.split_once('{') {
+            output_path.push_str(literal);
+            let (placeholder, after_placeholder) =
+                after_brace.split_once('}').with_context(|| {
+                    format!("unterminated placeholder in output template {template:?}")
+                })?;
+            let value: Cow<str> = match placeholder {
+                "regex" => self.regex.as_str().into(),
+                "throughput" => match traces.throughput {
+                    Some(ThroughputType::Bytes | ThroughputType::BytesDecimal) => "bytes",
+                    Some(ThroughputType::Elements) => "elements",
+                    None => "time",
+                }
+                .into(),
+                "date" => date::format_date(
+                    traces
+                        .measurement_times
+                        .map_or_else(SystemTime::now, |(_, newest)| newest),
+                )
+                .into(),
+                "ext" => self
+                    .output_path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+                other => bail!("unknown placeholder {{{other}}} in output template {template:?}"),
+            };
+            output_path.extend(value.chars().map(|c| {
+                if c.is_alphanumeric() || "-_.+=,".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            }));
+            rest = after_placeholder;
+        }
+        output_path.push_str(rest);
+        Ok(Self {
+            output_path: PathBuf::from(output_path).into(),
+            output_template: None,
+            ..self.clone()
+        })
+    }
+
+    /// Configuration of the plot of one trace group in `--split-output` mode
+    ///
+    /// The plot is titled after the group and written to a file named after
+    /// it. Colors are assigned to the legend labels of all groups.
+    pub fn split_args(&self, group: &str, color_names: Vec<Box<str>>) -> Self {
+        let stem = self.output_path.file_stem().unwrap_or_default();
+        let mut file_name = stem.to_os_string();
+        file_name.push(format!("-{group}"));
+        if let Some(extension) = self.output_path.extension() {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+        Self {
+            title: if self.title.is_empty() {
+                group.into()
+            } else {
+                format!("{}: {group}", self.title).into()
+            },
+            output_path: self.output_path.with_file_name(file_name).into(),
+            split_output: false,
+            color_names,
+            ..self.clone()
+        }
+    }
+
+    /// Label of the horizontal axis of line charts
+    ///
+    /// This is `--x-label`, unless it is `auto`, in which case a label is
+    /// picked according to the throughput type of the traces. The unit from
+    /// `--x-unit` is then appended, if any.
+    pub fn x_label(&self, traces: &Traces) -> String {
+        let label = if &*self.x_label != "auto" {
+            &self.x_label
+        } else if !self.track.is_empty() {
+            "Snapshot"
+        } else {
+            match traces.throughput {
+                Some(ThroughputType::Bytes | ThroughputType::BytesDecimal) => "Bytes",
+                Some(ThroughputType::Elements) => "Elements",
+                None => "Input size",
+            }
+        };
+        match self.x_unit.as_deref() {
+            None => label.to_string(),
+            Some("auto") => match traces.throughput {
+                Some(ThroughputType::Bytes | ThroughputType::BytesDecimal) => {
+                    format!("{label} [bytes]")
+                }
+                Some(ThroughputType::Elements) => format!("{label} [elements]"),
+                None => label.to_string(),
+            },
+            Some(unit) => format!("{label} [{unit}]"),
+        }
+    }
+
+    /// Colors used in the plot
+    pub fn theme(&self) -> Cow<'_, Theme> {
+        self.theme
+            .as_ref()
+            .map_or_else(|| Cow::Owned(Theme::default()), Cow::Borrowed)
+    }
+
+    /// Label of baseline traces in the legend, if a baseline is used
+    pub fn baseline_label(&self) -> Option<&str> {
+        self.baseline
+            .as_deref()
+            .map(|baseline| self.baseline_label.as_deref().unwrap_or(baseline))
+    }
+
+    /// Report a data quality issue, which is an error in strict mode
+    pub fn warn(&self, message: impl Display) -> Result<()> {
+        if self.strict {
+            bail!("{message}")
+        }
+        eprintln!("warning: {message}");
+        Ok(())
+    }
+}
+
+/// Load the traces selected by some configuration from Criterion data
+///
+/// This also loads baseline data, if requested, and applies all requested
+/// post-processing to the resulting traces.
+pub fn load_traces(args: &Args) -> Result<Traces> {
+    // Tracked snapshots are loaded separately, then assembled
+    if !args.track.is_empty() {
+        return load_tracked_traces(args);
+    }
+
+    // Load data points from Criterion
+    let data = criterion::read_all(args).context("loading data from Criterion")?;
+
+    // Rearrange data in a layout suitable for plotting
+    let mut traces = Traces::new(args, data).context("rearranging data into plot traces")?;
+
+    // Load the baseline to be compared against, if any
+    if let Some(baseline) = &args.baseline {
+        let data = criterion::read_dataset(args, baseline)
+            .context("loading baseline data from Criterion")?;
+        let baseline =
+            Traces::new(args, data).context("rearranging baseline data into plot traces")?;
+        traces
+            .attach_baseline(baseline)
+            .context("comparing data with baseline")?;
+    }
+
+    // Make sure that data points are sorted, as plotting assumes
+    if !args.assume_sorted {
+        for name in traces.sort_points().context("sorting data points")? {
+            args.warn(format_args!(
+                "data points of trace {name} were out of order and had to be sorted"
+            ))?;
+        }
+    }
+
+    // Post-process the traces
+    if let Some(pattern) = &args.merge {
+        traces.merge(pattern).context("merging traces")?;
+    }
+    traces.transform(args.transform);
+    if let Some(window) = args.smooth_window {
+        traces.smooth(window.get());
+    }
+    if let Some(max_error_factor) = args.max_error_factor {
+        traces
+            .clamp_errors(max_error_factor)
+            .context("clamping error bars")?;
+    }
+    if args.drop_flat {
+        for (name, spread) in traces.drop_flat(args.flatness_threshold) {
+            eprintln!(
+                "note: not plotting trace {name} as its measurements are flat \
+                (relative standard deviation {:.2}%)",
+                spread * 100.0
+            );
+        }
+    }
+    if let Some(max_traces) = args.max_traces() {
+        traces.limit(max_traces, args.trace_limit_strategy);
+    }
+    if args.interpolate_missing {
+        let num_added = traces.interpolate_missing();
+        if num_added > 0 {
+            eprintln!(
+                "note: plotting {num_added} interpolated data points, which were not measured"
+            );
+        }
+    }
+    let comparison = if let Some(reference) = &args.diff_to {
+        Some(Comparison::Difference(reference.clone()))
+    } else if let Some(reference) = &args.relative_to {
+        Some(Comparison::Ratio(reference.clone()))
+    } else if args.relative_to_first {
+        traces
+            .per_trace_data
+            .first()
+            .map(|trace| Comparison::Ratio(trace.name.clone()))
+    } else {
+        None
+    };
+    if let Some(comparison) = comparison {
+        traces
+            .compare_to(comparison)
+            .context("comparing traces with the reference trace")?;
+    }
+    traces.to_metric(args.metric);
+
+    for name in traces.hide(&args.hide) {
+        args.warn(format_args!(
+            "cannot hide trace {name}, as no trace has this name"
+        ))?;
+    }
+
+    // Abort if there is nothing to plot
+    if traces.is_empty() {
+        bail!("specified regex does not select any trace")
+    }
+    Ok(traces)
+}
+
+/// Load the traces selected by some configuration from each `--track`
+/// snapshot, then assemble them into traces of the evolution of performance
+fn load_tracked_traces(args: &Args) -> Result<Traces> {
+    let problem_size = args
+        .problem_size
+        .context("tracking snapshots requires a problem size")?;
+
+    // Only keep the snapshots that fall within the --since/--until window
+    let mut selected = Vec::with_capacity(args.track.len());
+    for snapshot in &args.track {
+        if args.since.is_some() || args.until.is_some() {
+            let date = snapshot.label.parse::<Date>().with_context(|| {
+                format!(
+                    "parsing the label of snapshot {} as a date for --since/--until",
+                    snapshot.label
+                )
+            })?;
+            if args.since.is_some_and(|since| date < since)
+                || args.until.is_some_and(|until| date > until)
+            {
+                continue;
+            }
+        }
+        selected.push(snapshot);
+    }
+    ensure!(
+        !selected.is_empty(),
+        "no tracked snapshot is dated within the --since/--until window"
+    );
+
+    // Load the traces of each snapshot
+    let snapshots = selected
+        .into_iter()
+        .map(|snapshot| {
+            let snapshot_args = Args {
+                input_path: snapshot.input_path.clone(),
+                track: Vec::new(),
+                ..args.clone()
+            };
+            let traces = load_traces(&snapshot_args)
+                .with_context(|| format!("loading traces from snapshot {}", snapshot.label))?;
+            Ok((snapshot.label.clone(), traces))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let traces = Traces::track(snapshots, problem_size).context("tracking snapshots")?;
+    ensure!(
+        !traces.is_empty(),
+        "no trace has data at problem size {problem_size} in the tracked snapshots"
+    );
+    Ok(traces)
+}
+
+/// Parse the --jitter fraction, which must be between 0 and 1
+fn parse_jitter(s: &str) -> Result<f64> {
+    let jitter = s.parse::<f64>().context("jitter should be a number")?;
+    ensure!(
+        (0.0..1.0).contains(&jitter),
+        "jitter should be between 0 and 1"
+    );
+    Ok(jitter)
+}
+
+/// Serialize a regex as its source text
+fn serialize_regex<S: Serializer>(regex: &Regex, serializer: S) -> Result<S::Ok, S::Error> {
+    regex.as_str().serialize(serializer)
+}
+
+/// Serialize an optional regex as its source text
+fn serialize_optional_regex<S: Serializer>(
+    regex: &Option<Regex>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    regex.as_ref().map(Regex::as_str).serialize(serializer)
+}
+
+/// Serialize a list of regexes as their source text
+fn serialize_regexes<S: Serializer>(regexes: &[Regex], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(regexes.iter().map(Regex::as_str))
+}
+
+/// Use anyhow for error handling convenience
+pub use anyhow::Result;
@@ -1,92 +1,126 @@
-mod criterion;
-mod plot;
-mod trace;
-
-use crate::trace::Traces;
-use anyhow::{bail, Context};
+use anyhow::{ensure, Context};
 use clap::Parser;
-use regex::Regex;
-use std::{num::NonZeroU32, path::Path};
-
-/// Simple bulk plotter from criterion data
-#[derive(Debug, Parser)]
-#[command(version, about)]
-struct Args {
-    /// Path to root of Rust project where criterion data was acquired
-    #[arg(short, long, default_value = ".")]
-    input_path: Box<Path>,
-
-    /// Name of output image
-    #[arg(short, long, default_value = "./output.svg")]
-    output_path: Box<Path>,
-
-    /// Width of the output image in pixels
-    #[arg(short = 'W', long, default_value = "1920")]
-    width: NonZeroU32,
-
-    /// Height of the output image in pixels
-    #[arg(short = 'H', long, default_value = "1080")]
-    height: NonZeroU32,
-
-    /// Title of the plot
-    #[arg(short, long, default_value = "Benchmark results")]
-    title: Box<str>,
+use multiplot::{export, plot, trace::Traces, Args, Result};
+use std::collections::BTreeSet;
 
-    /// Forced lower bound of the Y axis
-    ///
-    /// Will automatically set the Y scale to fit all traces by default
-    #[arg(short = 'y', long, default_value = None)]
-    min_y: Option<f32>,
+fn main() -> Result<()> {
+    // Parse CLI arguments
+    let args = Args::parse();
 
-    /// Forced upper bound of the Y axis
-    ///
-    /// Will automatically set the Y scale to fit all traces by default
-    #[arg(short = 'Y', long, default_value = None)]
-    max_y: Option<f32>,
+    // Draw a grid of plots if multiple regexes were specified
+    if !args.also_regex.is_empty() {
+        ensure!(
+            args.csv_output.is_none(),
+            "CSV export is not supported when plotting multiple regexes"
+        );
+        ensure!(
+            args.markdown.is_none(),
+            "Markdown export is not supported when plotting multiple regexes"
+        );
+        ensure!(
+            args.html_output.is_none(),
+            "HTML export is not supported when plotting multiple regexes"
+        );
+        ensure!(
+            !args.manifest,
+            "manifests are not supported when plotting multiple regexes"
+        );
+        ensure!(
+            !args.score,
+            "scores are not supported when plotting multiple regexes"
+        );
+        let panels = args
+            .panel_args()
+            .map(|panel_args| {
+                let traces = multiplot::load_traces(&panel_args)
+                    .with_context(|| format!("loading traces for regex {}", panel_args.regex))?;
+                Ok((panel_args, traces))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let args = args.expand_output_template(&panels[0].1)?;
+        return plot::draw_grid(&args, panels).context("drawing the performance plots");
+    }
 
-    /// Unit of element-based throughput measurement
-    ///
-    /// This will be used, along with an SI prefix and a "per second" suffix, to
-    /// label the plot's vertical axis in the presence of such measurements.
-    #[arg(short, long, default_value = "FLOP")]
-    element_throughput_unit: Box<str>,
+    // Load the traces to be plotted, then name the output after them
+    let traces = multiplot::load_traces(&args)?;
+    let args = args.expand_output_template(&traces)?;
 
-    /// Label of the horizontal axis
-    ///
-    /// Depending on the project, this can be an input size or an iteration
-    /// count, so we need full control over labeling there.
-    #[arg(short, long, default_value = "Input size (f32s)")]
-    x_label: Box<str>,
+    // Export the data if requested
+    if let Some(csv_path) = &args.csv_output {
+        export::write_csv(&args, &traces, csv_path).context("exporting data to CSV")?;
+    }
+    if let Some(markdown_path) = &args.markdown {
+        export::write_markdown(&traces, markdown_path, args.precision)
+            .context("exporting data to a Markdown table")?;
+    }
+    if let Some(html_path) = &args.html_output {
+        export::write_html(&args, &traces, html_path).context("exporting an HTML page")?;
+    }
+    if args.score {
+        export::print_scores(&traces, args.precision)?;
+    }
 
-    /// Regex matching the traces to be plotted
-    regex: Regex,
-}
-//
-impl Args {
-    /// Plot size in plotters's expected format
-    fn plot_size(&self) -> (u32, u32) {
-        (self.width.get(), self.height.get())
+    // Draw the plot(s) and their manifest, then fail if the traces regressed
+    // too much
+    let regressions = find_regressions(&args, &traces)?;
+    if args.split_output {
+        let groups = traces.split();
+        let color_names = groups
+            .iter()
+            .flat_map(|(_, traces)| traces.per_trace_data.iter())
+            .map(|trace| trace.name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if let Some(legend_path) = &args.legend_file {
+            let key_args = Args {
+                color_names: color_names.clone(),
+                ..args.clone()
+            };
+            export::write_legend_file(&key_args, legend_path).context("writing the legend file")?;
+        }
+        for (group, traces) in groups {
+            let group_args = args.split_args(&group, color_names.clone());
+            draw_with_manifest(&group_args, traces)
+                .with_context(|| format!("plotting trace group {group}"))?;
+        }
+    } else {
+        draw_with_manifest(&args, traces)?;
     }
+    check_regressions(&args, &regressions)
 }
-//
-fn main() -> Result<()> {
-    // Parse CLI arguments
-    let args = Args::parse();
-
-    // Load data points from Criterion
-    let data = criterion::read_all(&args).context("loading data from Criterion")?;
-
-    // Rearrange data in a layout suitable for plotting
-    let traces = Traces::new(data).context("rearranging data into plot traces")?;
 
-    // Abort if there is nothing to plot
-    if traces.is_empty() {
-        bail!("specified regex does not select any trace")
+/// Draw a plot, along with its manifest if requested
+fn draw_with_manifest(args: &Args, traces: Traces) -> Result<()> {
+    let manifest_traces = args.manifest.then(|| traces.clone());
+    plot::draw(args, traces).context("drawing the performance plot")?;
+    if let Some(traces) = manifest_traces {
+        export::write_manifest(args, &traces).context("writing the plot's manifest")?;
     }
+    Ok(())
+}
 
-    // Draw the plot
-    plot::draw(&args, traces).context("drawing the performance plot")
+/// Describe the regressions that exceed the `--fail-on-regression` threshold
+fn find_regressions(args: &Args, traces: &Traces) -> Result<Vec<String>> {
+    let Some(threshold) = args.fail_on_regression else {
+        return Ok(Vec::new());
+    };
+    Ok(traces
+        .regressions(args, threshold)?
+        .into_iter()
+        .map(|(name, x, regression)| {
+            format!("{name} regressed by {regression:.1}% at problem size {x}")
+        })
+        .collect())
 }
 
-/// Use anyhow for error handling convenience
-pub use anyhow::Result;
+/// Fail if any regression exceeded the `--fail-on-regression` threshold
+fn check_regressions(args: &Args, regressions: &[String]) -> Result<()> {
+    ensure!(
+        regressions.is_empty(),
+        "some traces regressed by more than {}% with respect to the baseline:\n- {}",
+        args.fail_on_regression.unwrap_or_default(),
+        regressions.join("\n- ")
+    );
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! Theoretical performance models that can be drawn over the measurements
+
+use crate::Result;
+use anyhow::{bail, ensure, Context};
+use serde::Serialize;
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::Peekable,
+    str::{CharIndices, FromStr},
+};
+
+/// Analytical function of the problem size `n`, e.g. `2e-9*n + 1e-6`
+///
+/// Models are made of numbers, the problem size `n`, the `+`, `-`, `*`, `/`
+/// and `^` operators, parentheses, and the `sqrt`, `exp`, `ln`, `log2` and
+/// `log10` functions. Their value is expressed in the unit of the vertical
+/// axis of the plot.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(into = "String")]
+pub struct Model {
+    /// Textual definition of the model, as specified by the user
+    source: Box<str>,
+
+    /// Parsed definition of the model
+    expr: Expr,
+}
+//
+impl Model {
+    /// Evaluate the model at some problem size
+    pub fn eval(&self, n: f64) -> f64 {
+        self.expr.eval(n)
+    }
+}
+//
+impl Display for Model {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+//
+impl From<Model> for String {
+    fn from(model: Model) -> Self {
+        model.source.into()
+    }
+}
+//
+impl FromStr for Model {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            source: s,
+            chars: s.char_indices().peekable(),
+        };
+        let expr = parser.sum()?;
+        if let Some(&(pos, c)) = parser.chars.peek() {
+            bail!("unexpected character {c:?} at position {pos}");
+        }
+        Ok(Self {
+            source: s.trim().into(),
+            expr,
+        })
+    }
+}
+
+/// Node of a model's expression tree
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    /// Numerical constant
+    Number(f64),
+
+    /// Problem size
+    ProblemSize,
+
+    /// Negation of an expression
+    Neg(Box<Expr>),
+
+    /// Binary operation
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+
+    /// Function applied to an expression
+    Call(Function, Box<Expr>),
+}
+//
+impl Expr {
+    /// Evaluate this expression at some problem size
+    fn eval(&self, n: f64) -> f64 {
+        match self {
+            Self::Number(value) => *value,
+            Self::ProblemSize => n,
+            Self::Neg(expr) => -expr.eval(n),
+            Self::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(n), rhs.eval(n));
+                match op {
+                    BinOp::Add => lhs + rhs,
+                    BinOp::Sub => lhs - rhs,
+                    BinOp::Mul => lhs * rhs,
+                    BinOp::Div => lhs / rhs,
+                    BinOp::Pow => lhs.powf(rhs),
+                }
+            }
+            Self::Call(function, expr) => {
+                let arg = expr.eval(n);
+                match function {
+                    Function::Sqrt => arg.sqrt(),
+                    Function::Exp => arg.exp(),
+                    Function::Ln => arg.ln(),
+                    Function::Log2 => arg.log2(),
+                    Function::Log10 => arg.log10(),
+                }
+            }
+        }
+    }
+}
+
+/// Binary operator that can be used in models
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum BinOp {
+    /// Addition (`+`)
+    Add,
+
+    /// Subtraction (`-`)
+    Sub,
+
+    /// Multiplication (`*`)
+    Mul,
+
+    /// Division (`/`)
+    Div,
+
+    /// Exponentiation (`^`)
+    Pow,
+}
+
+/// Function that can be used in models
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Function {
+    /// Square root
+    Sqrt,
+
+    /// Exponential
+    Exp,
+
+    /// Natural logarithm
+    Ln,
+
+    /// Base 2 logarithm
+    Log2,
+
+    /// Base 10 logarithm
+    Log10,
+}
+
+/// Recursive descent parser of model expressions
+struct Parser<'source> {
+    /// Full text of the model
+    source: &'source str,
+
+    /// Characters that remain to be parsed
+    chars: Peekable<CharIndices<'source>>,
+}
+//
+impl Parser<'_> {
+    /// Parse a sum or difference of products
+    fn sum(&mut self) -> Result<Expr> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.next_operator(&[('+', BinOp::Add), ('-', BinOp::Sub)]) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    /// Parse a product or quotient of signed powers
+    fn product(&mut self) -> Result<Expr> {
+        let mut expr = self.signed()?;
+        while let Some(op) = self.next_operator(&[('*', BinOp::Mul), ('/', BinOp::Div)]) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.signed()?));
+        }
+        Ok(expr)
+    }
+
+    /// Parse a power, possibly preceded by a minus sign
+    fn signed(&mut self) -> Result<Expr> {
+        if self.next_operator(&[('-', BinOp::Sub)]).is_some() {
+            Ok(Expr::Neg(Box::new(self.signed()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    /// Parse an atom, possibly raised to a (right-associative) power
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.atom()?;
+        if let Some(op) = self.next_operator(&[('^', BinOp::Pow)]) {
+            Ok(Expr::Binary(Box::new(base), op, Box::new(self.signed()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// Parse a number, the problem size, a function call or a parenthesized
+    /// expression
+    fn atom(&mut self) -> Result<Expr> {
+        self.skip_whitespace();
+        let Some(&(start, c)) = self.chars.peek() else {
+            bail!("unexpected end of model");
+        };
+        if c == '(' {
+            self.chars.next();
+            let expr = self.sum()?;
+            self.expect(')')?;
+            Ok(expr)
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            let mut prev = c;
+            while let Some(&(pos, c)) = self.chars.peek() {
+                let in_exponent = (c == '-' || c == '+') && (prev == 'e' || prev == 'E');
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || in_exponent) {
+                    break;
+                }
+                end = pos + c.len_utf8();
+                prev = c;
+                self.chars.next();
+            }
+            let number = &self.source[start..end];
+            Ok(Expr::Number(number.parse().with_context(|| {
+                format!("invalid number {number:?} at position {start}")
+            })?))
+        } else if c.is_ascii_alphabetic() {
+            let mut end = start;
+            while let Some(&(pos, c)) = self.chars.peek() {
+                if !c.is_ascii_alphanumeric() {
+                    break;
+                }
+                end = pos + 1;
+                self.chars.next();
+            }
+            let function = match &self.source[start..end] {
+                "n" => return Ok(Expr::ProblemSize),
+                "sqrt" => Function::Sqrt,
+                "exp" => Function::Exp,
+                "ln" => Function::Ln,
+                "log2" => Function::Log2,
+                "log10" => Function::Log10,
+                other => bail!(
+                    "unknown identifier {other:?} at position {start}, expected n or a function name"
+                ),
+            };
+            self.expect('(')?;
+            let arg = self.sum()?;
+            self.expect(')')?;
+            Ok(Expr::Call(function, Box::new(arg)))
+        } else {
+            bail!("unexpected character {c:?} at position {start}")
+        }
+    }
+
+    /// Consume the next character if it is one of the specified operators,
+    /// which are listed along with their textual representation
+    fn next_operator(&mut self, operators: &[(char, BinOp)]) -> Option<BinOp> {
+        self.skip_whitespace();
+        let &(_, c) = self.chars.peek()?;
+        let &(_, op) = operators.iter().find(|(symbol, _)| *symbol == c)?;
+        self.chars.next();
+        Some(op)
+    }
+
+    /// Consume the next character, which must be `expected`
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        let next = self.chars.next();
+        ensure!(
+            next.map(|(_, c)| c) == Some(expected),
+            "expected {expected:?} at position {}",
+            next.map_or(self.source.len(), |(pos, _)| pos)
+        );
+        Ok(())
+    }
+
+    /// Skip whitespace between tokens
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+}
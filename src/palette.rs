@@ -0,0 +1,93 @@
+//! Reduction of bitmap images to an indexed color palette
+
+use crate::Result;
+use anyhow::{bail, ensure, Context};
+use color_quant::NeuQuant;
+use png::{BitDepth, ColorType, Compression, Decoder, Encoder};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+/// Rewrite a PNG image with an indexed palette of `2^depth` colors
+///
+/// The palette is picked by a neural network quantizer, so that the colors of
+/// the image are approximated as well as possible. This shrinks the file
+/// considerably, since plots usually feature very few colors.
+pub fn quantize_png(path: &Path, depth: u8) -> Result<()> {
+    ensure!(
+        path.extension().is_some_and(|extension| extension == "png"),
+        "reducing the color palette is only supported for PNG images"
+    );
+    ensure!(
+        (1..=8).contains(&depth),
+        "palette depth should be 1 to 8 bits"
+    );
+
+    // Decode the image as 8-bit RGBA
+    let file = File::open(path).context("opening the image")?;
+    let mut reader = Decoder::new(BufReader::new(file))
+        .read_info()
+        .context("decoding the image header")?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .context("decoding the image")?;
+    ensure!(
+        info.bit_depth == BitDepth::Eight,
+        "expected an 8-bit image, got {:?}",
+        info.bit_depth
+    );
+    let buffer = &buffer[..info.buffer_size()];
+    let rgba = match info.color_type {
+        ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+            .collect::<Vec<_>>(),
+        ColorType::Rgba => buffer.to_vec(),
+        other => bail!("expected an RGB or RGBA image, got {other:?}"),
+    };
+
+    // Pick the palette and map each pixel to its closest palette entry
+    let quantizer = NeuQuant::new(10, 1 << depth, &rgba);
+    let indices = rgba
+        .chunks_exact(4)
+        .map(|pixel| quantizer.index_of(pixel) as u8)
+        .collect::<Vec<_>>();
+
+    // PNG only supports some bit depths, pick the smallest one that fits and
+    // pack the pixels of each row accordingly
+    let (bit_depth, bits) = match depth {
+        1 => (BitDepth::One, 1),
+        2 => (BitDepth::Two, 2),
+        3 | 4 => (BitDepth::Four, 4),
+        _ => (BitDepth::Eight, 8),
+    };
+    let pixels_per_byte = 8 / bits;
+    let packed = indices
+        .chunks_exact(info.width as usize)
+        .flat_map(|row| {
+            row.chunks(pixels_per_byte).map(|pixels| {
+                pixels.iter().enumerate().fold(0u8, |byte, (idx, &pixel)| {
+                    byte | (pixel << (8 - bits * (idx + 1)))
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Encode the indexed image in place of the original one
+    let file = File::create(path).context("creating the indexed image")?;
+    let mut encoder = Encoder::new(BufWriter::new(file), info.width, info.height);
+    encoder.set_color(ColorType::Indexed);
+    encoder.set_depth(bit_depth);
+    encoder.set_palette(quantizer.color_map_rgb());
+    encoder.set_compression(Compression::Best);
+    let mut writer = encoder
+        .write_header()
+        .context("encoding the indexed image header")?;
+    writer
+        .write_image_data(&packed)
+        .context("encoding the indexed image")?;
+    writer.finish().context("writing the indexed image")
+}
@@ -1,163 +1,1874 @@
 //! Where traces get drawn into a plot
 
-use crate::{criterion::ThroughputType, trace::Traces, Args, Result};
-use anyhow::Context;
+use crate::{
+    criterion::ThroughputType,
+    date, format, palette, theme,
+    trace::{Comparison, MeasurementDisplay, Metric, Trace, Traces},
+    Args, Result,
+};
+use anyhow::{bail, ensure, Context};
+use clap::ValueEnum;
 use colorous::SINEBOW;
-use plotters::{backend::RGBPixel, prelude::*};
+use plotters::{
+    backend::RGBPixel,
+    coord::{
+        ranged1d::{Ranged, ValueFormatter},
+        Shift,
+    },
+    prelude::*,
+    style::{
+        text_anchor::{HPos, Pos, VPos},
+        SizeDesc,
+    },
+};
 use plotters_backend::{
     BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind,
 };
+use serde::Serialize;
 use std::{
     borrow::Cow,
     error::Error,
     fmt::{self, Display, Formatter},
-    ops::{Deref, DerefMut},
+    io::Write,
+    ops::{Deref, DerefMut, Range},
     path::Path,
+    str::FromStr,
+    time::SystemTime,
 };
 
-/// Draw the plot
+/// Kind of plot to be drawn
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlotKind {
+    /// Line chart of each trace's measurements across problem sizes
+    #[default]
+    Line,
+
+    /// One row per trace showing its confidence interval, sorted by point
+    /// estimate (each trace must have a single data point)
+    Caterpillar,
+
+    /// Scatter plot of each trace's measurements across problem sizes, where
+    /// the radius of each point is proportional to the relative width of its
+    /// confidence interval
+    ScatterSized,
+
+    /// Heatmap whose cell at row i and column j is the speedup of trace i
+    /// over trace j (each trace must have a single data point)
+    SpeedupMatrix,
+}
+
+/// Part of each measurement that the lines of line charts go through
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineSource {
+    /// Point estimate
+    #[default]
+    Point,
+
+    /// Lower bound of the confidence interval
+    Lower,
+
+    /// Upper bound of the confidence interval
+    Upper,
+}
+//
+impl LineSource {
+    /// Pick the matching part of a measurement
+    pub fn value(self, meas: &MeasurementDisplay) -> f32 {
+        match self {
+            Self::Point => meas.point_estimate,
+            Self::Lower => meas.lower_bound,
+            Self::Upper => meas.upper_bound,
+        }
+    }
+}
+
+/// Performance tier, drawn as a horizontal band behind line charts
+///
+/// On the command line, this is specified as `<value>=<color>`, where the
+/// value is the start of the tier on the vertical axis and the color uses
+/// the `#rrggbb` notation of theme files.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Tier {
+    /// Vertical coordinate where the tier starts
+    pub start: f32,
+
+    /// Color of the tier's band, which is drawn faintly
+    pub color: theme::Color,
+}
+//
+impl FromStr for Tier {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, color) = s
+            .split_once('=')
+            .context("performance tiers should have the <value>=<color> format")?;
+        Ok(Self {
+            start: start
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid tier start {start:?}"))?,
+            color: theme::Color::try_from(color.trim().to_string())?,
+        })
+    }
+}
+
+/// Target performance window, drawn as a shaded horizontal band behind line
+/// charts
+///
+/// On the command line, this is specified as `<lower>,<upper>`, where both
+/// bounds are coordinates on the vertical axis.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct TargetBand {
+    /// Vertical coordinate of the bottom of the window
+    pub lower: f32,
+
+    /// Vertical coordinate of the top of the window
+    pub upper: f32,
+}
+//
+impl FromStr for TargetBand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (lower, upper) = s
+            .split_once(',')
+            .context("target bands should have the <lower>,<upper> format")?;
+        let parse_bound = |bound: &str| -> Result<f32> {
+            bound
+                .trim()
+                .parse::<f32>()
+                .with_context(|| format!("invalid target band bound {bound:?}"))
+        };
+        let (lower, upper) = (parse_bound(lower)?, parse_bound(upper)?);
+        ensure!(
+            lower < upper,
+            "the lower bound of the target band should be below its upper bound"
+        );
+        Ok(Self { lower, upper })
+    }
+}
+
+/// Subrange of the color gradient from which trace colors are picked
+///
+/// On the command line, this is specified as `<start>,<end>`, where both
+/// bounds are positions along the gradient, between 0 and 1.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct ColorRange {
+    /// Position of the first trace color along the gradient
+    pub start: f64,
+
+    /// Position along the gradient where trace colors stop
+    pub end: f64,
+}
+//
+impl ColorRange {
+    /// Map a position in [0, 1] into this subrange of the gradient
+    fn remap(&self, position: f64) -> f64 {
+        self.start + position * (self.end - self.start)
+    }
+}
+//
+impl Default for ColorRange {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+}
+//
+impl FromStr for ColorRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once(',')
+            .context("color ranges should have the <start>,<end> format")?;
+        let parse_bound = |bound: &str| -> Result<f64> {
+            let value = bound
+                .trim()
+                .parse::<f64>()
+                .with_context(|| format!("invalid color range bound {bound:?}"))?;
+            ensure!(
+                (0.0..=1.0).contains(&value),
+                "color range bounds should be between 0 and 1, got {value}"
+            );
+            Ok(value)
+        };
+        let (start, end) = (parse_bound(start)?, parse_bound(end)?);
+        ensure!(
+            start < end,
+            "the start of the color range should be below its end"
+        );
+        Ok(Self { start, end })
+    }
+}
+
+/// Labels of the ticks of the horizontal axis
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum XTickLabels {
+    /// Powers of ten, spread along the axis
+    #[default]
+    Powers,
+
+    /// Criterion's textual description of each measured problem size
+    ValueStr,
+}
+
+/// Display of the confidence intervals of line charts
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorStyle {
+    /// One error bar per data point
+    #[default]
+    Bars,
+
+    /// A translucent band that goes through the bounds of all data points
+    Band,
+}
+
+/// Position of the mesh gridlines of line charts with respect to the traces
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Gridlines {
+    /// Gridlines are hidden by the traces
+    #[default]
+    Behind,
+
+    /// Gridlines are drawn again on top of the traces
+    Front,
+}
+
+/// Borders of the chart that are drawn
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Spines {
+    /// Box around the whole chart
+    Box,
+
+    /// Left and bottom axes only
+    #[default]
+    Lr,
+}
+
+/// Position of the legend, when drawn outside of the chart
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LegendOutside {
+    /// To the right of the chart
+    Right,
+
+    /// Below the chart
+    Bottom,
+}
+
+/// Draw the plot into the output file(s)
 pub fn draw(args: &Args, traces: Traces) -> Result<()> {
+    for (path, size) in args.outputs() {
+        draw_to_file(args, &path, size, |root| {
+            draw_on(args, traces.clone(), root)
+        })
+        .with_context(|| format!("drawing {}", path.display()))?;
+        reduce_palette(args, &path)?;
+    }
+    Ok(())
+}
+
+/// Draw a grid of plots into the output file(s)
+///
+/// Each panel is drawn with its own configuration and traces, while the
+/// overall configuration is used for the output file and overall title.
+pub fn draw_grid(args: &Args, panels: Vec<(Args, Traces)>) -> Result<()> {
+    for (path, size) in args.outputs() {
+        draw_to_file(args, &path, size, |root| {
+            draw_grid_on(args, panels.clone(), root)
+        })
+        .with_context(|| format!("drawing {}", path.display()))?;
+        reduce_palette(args, &path)?;
+    }
+    Ok(())
+}
+
+/// Reduce an output image to an indexed color palette, if requested
+fn reduce_palette(args: &Args, path: &Path) -> Result<()> {
+    if let Some(depth) = args.palette_depth.filter(|_| !args.preview) {
+        palette::quantize_png(path, depth)
+            .with_context(|| format!("reducing the color palette of {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Draw the plot as SVG into an arbitrary writer
+///
+/// This does not touch the filesystem, which makes it suitable for streaming
+/// plots into sockets, archive entries or in-memory buffers.
+pub fn draw_svg_to_writer<W: Write>(args: &Args, traces: Traces, mut writer: W) -> Result<()> {
+    let mut svg = String::new();
+    draw_with_backend(
+        args,
+        DrawingBackendImpl::svg_string(&mut svg, args.plot_size()),
+        |root| draw_on(args, traces, root),
+    )?;
+    writer
+        .write_all(svg.as_bytes())
+        .context("writing the SVG plot")
+}
+
+/// Draw into an output file, picking the drawing backend from its extension
+///
+/// Plotters' backends write their output when they are presented, or when
+/// they are dropped without having been presented, in which case errors are
+/// ignored. The bitmap backend reports write errors on present, but the SVG
+/// backend writes files through a `BufWriter` that it does not flush, so
+/// errors that occur when the last bytes are written get lost on drop. SVG
+/// plots are therefore rendered into memory, then written here.
+fn draw_to_file(
+    args: &Args,
+    path: &Path,
+    size: (u32, u32),
+    draw: impl for<'target> FnOnce(&DrawingArea<DrawingBackendImpl<'target>, Shift>) -> Result<()>,
+) -> Result<()> {
+    let extension = path
+        .extension()
+        .context("need file extension to pick backend")?;
+    if extension == "svg" {
+        let mut svg = String::new();
+        draw_with_backend(args, DrawingBackendImpl::svg_string(&mut svg, size), draw)?;
+        check_written(
+            args,
+            std::fs::write(path, svg).context("failed to write the plot"),
+        )
+    } else {
+        let backend = if args.no_antialias {
+            DrawingBackendImpl::AliasedBitmap(AliasedBitmap(BitMapBackend::new(path, size)))
+        } else {
+            DrawingBackendImpl::bitmap(path, size)
+        };
+        draw_with_backend(args, backend, draw)
+    }
+}
+
+/// Report a failure to write the plot, which is only a warning with
+/// `--no-present-check`
+fn check_written(args: &Args, result: Result<()>) -> Result<()> {
+    match result {
+        Err(e) if args.no_present_check => {
+            eprintln!("warning: {e:#}");
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Draw using a pre-configured drawing backend
+fn draw_with_backend<'target>(
+    args: &Args,
+    backend: DrawingBackendImpl<'target>,
+    draw: impl FnOnce(&DrawingArea<DrawingBackendImpl<'target>, Shift>) -> Result<()>,
+) -> Result<()> {
     // Set up the drawing area
-    let root = DrawingBackendImpl::new(&args.output_path, args.plot_size())
-        .context("setting up the plot's drawing area")?
-        .into_drawing_area();
-    root.fill(&WHITE)
+    let root = backend.into_drawing_area();
+    root.fill(&args.theme().background.0)
         .context("filling the plot's drawing area")?;
 
-    // Determine the plotting range
-    let (x_range, mut y_range) = traces.xy_range();
-    if let Some(min_y) = args.min_y {
-        y_range.start = min_y;
-    } else {
-        y_range.start *= 0.5;
+    // Draw the plot
+    draw(&root)?;
+
+    // Manually call present to avoid errors being silently ignored on drop
+    check_written(args, root.present().context("failed to write the plot"))
+}
+
+/// Draw the plot into an existing drawing area
+///
+/// This lets library users embed the plot as one panel of a larger plotters
+/// figure. The drawing area is not filled with a background color and it is
+/// the caller's responsibility to call `present()` on the root drawing area
+/// once done.
+pub fn draw_on<DB: DrawingBackend>(
+    args: &Args,
+    traces: Traces,
+    area: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Draw the statistics footnote, if requested
+    if args.show_stats {
+        draw_stats(args, &traces, area)?;
+    }
+
+    // Draw the requested kind of plot
+    match args.kind() {
+        PlotKind::Line | PlotKind::ScatterSized if !args.panels.is_empty() => {
+            draw_panels(args, traces, area)
+        }
+        PlotKind::Line | PlotKind::ScatterSized => draw_lines(args, traces, area),
+        PlotKind::Caterpillar => {
+            ensure!(
+                args.panels.is_empty(),
+                "caterpillar plots cannot be split into panels"
+            );
+            draw_caterpillar(args, traces, area)
+        }
+        PlotKind::SpeedupMatrix => {
+            ensure!(
+                args.panels.is_empty(),
+                "speedup matrices cannot be split into panels"
+            );
+            draw_speedup_matrix(args, traces, area)
+        }
+    }
+}
+
+/// Draw a grid of plots into an existing drawing area
+///
+/// See [`draw_on()`] for the caller's responsibilities and [`draw_grid()`] for
+/// the meaning of the parameters.
+pub fn draw_grid_on<DB: DrawingBackend>(
+    args: &Args,
+    panels: Vec<(Args, Traces)>,
+    area: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Draw the overall title, if any
+    let area = if args.title.is_empty() {
+        area.clone()
+    } else {
+        let theme = args.theme();
+        let font_size = 0.05 * area.dim_in_pixel().1 as f64;
+        let style = TextStyle::from((&*args.font, font_size).into_font()).color(&theme.text.0);
+        area.titled(&args.title, style)
+            .context("drawing the overall title")?
+    };
+
+    // Split the drawing area into a grid that is as square as possible
+    let num_panels = panels.len();
+    let num_columns = (num_panels as f64).sqrt().ceil() as usize;
+    let num_rows = num_panels.div_ceil(num_columns);
+    let panel_areas = area.split_evenly((num_rows, num_columns));
+
+    // Draw each panel
+    for (panel_area, (panel_args, traces)) in panel_areas.iter().zip(panels) {
+        draw_on(&panel_args, traces, panel_area)
+            .with_context(|| format!("drawing the plot for regex {}", panel_args.regex))?;
+    }
+    Ok(())
+}
+
+/// Draw a footnote with statistics about the data in a corner of the area
+fn draw_stats<DB: DrawingBackend>(
+    args: &Args,
+    traces: &Traces,
+    area: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let mut stats = format!(
+        "{} benchmarks, {} data points",
+        traces.num_benchmarks,
+        traces.num_points()
+    );
+    if let Some(interval) = args.percentile_interval {
+        stats.push_str(&format!(", {interval} percentile intervals"));
+    } else if let Some(level) = traces.confidence_level {
+        stats.push_str(&format!(", {}% confidence intervals", level * 100.0));
+    }
+    if let Some((oldest, newest)) = traces.measurement_times {
+        let (oldest, newest) = (date::format_date(oldest), date::format_date(newest));
+        if oldest == newest {
+            stats.push_str(&format!(", measured {oldest}"));
+        } else {
+            stats.push_str(&format!(", measured {oldest} to {newest}"));
+        }
+    }
+    stats.push_str(&format!(
+        ", generated {}",
+        date::format_date(SystemTime::now())
+    ));
+    let (width, height) = area.dim_in_pixel();
+    let font_size = 0.02 * height as f64;
+    let theme = args.theme();
+    let style = TextStyle::from((&*args.font, font_size).into_font())
+        .color(&theme.text.0)
+        .pos(Pos::new(HPos::Right, VPos::Bottom));
+    area.draw(&Text::new(
+        stats,
+        (width as i32 - 5, height as i32 - 5),
+        style,
+    ))
+    .context("drawing the statistics footnote")
+}
+
+/// Draw a line chart or a scatter plot of the traces
+fn draw_lines<DB: DrawingBackend>(
+    args: &Args,
+    traces: Traces,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Determine the plotting range
+    let (x_range, y_range) = line_chart_ranges(args, &traces);
+    let is_linear = is_linear(&traces);
+
+    // Warn about forced vertical ranges that hide all data points
+    if args.min_y.is_some() || args.max_y.is_some() {
+        let visible = traces
+            .per_trace_data
+            .iter()
+            .flat_map(|trace| trace.data.iter())
+            .any(|(_, meas)| (y_range.start..=y_range.end).contains(&meas.point_estimate));
+        if !visible {
+            args.warn(format_args!(
+                "no data point lies within the forced vertical range {}..{}, the plot will be empty",
+                y_range.start, y_range.end
+            ))?;
+        }
+    }
+
+    // Pick the trace colors
+    let colors = trace_colors(args, &traces);
+
+    // Enumerate legend entries
+    let baseline_label = args.baseline_label().unwrap_or_default();
+    let mut legend_entries = Vec::new();
+    for (trace, &color) in traces.per_trace_data.iter().zip(&colors) {
+        legend_entries.push(LegendEntry {
+            label: Cow::Borrowed(&*trace.name),
+            color,
+            dashed: false,
+        });
+        if trace.baseline.is_some() {
+            legend_entries.push(LegendEntry {
+                label: Cow::Owned(format!("{} ({baseline_label})", trace.name)),
+                color,
+                dashed: true,
+            });
+        }
+    }
+    if let Some(model) = &args.model {
+        legend_entries.push(LegendEntry {
+            label: Cow::Owned(format!("Model: {model}")),
+            color: args.theme().reference_line.opaque(),
+            dashed: false,
+        });
+    }
+    let num_legend_entries = legend_entries.len();
+
+    // Draw the legend outside of the chart, if requested
+    let chart_area = if let Some(position) = args.legend_outside {
+        draw_outside_legend(args, root, position, &legend_entries)?
+    } else {
+        root.clone()
+    };
+
+    // Draw the confidence interval track below the chart, if requested
+    let chart_area = if args.ci_track {
+        let (_, height) = chart_area.dim_in_pixel();
+        let (chart_area, track_area) = chart_area.split_vertically(height * 4 / 5);
+        let margin = 1.percent().in_pixels(&chart_area);
+        draw_ci_track(args, &traces, &colors, x_range.clone(), margin, &track_area)?;
+        chart_area
+    } else {
+        chart_area
+    };
+
+    // Set up the chart
+    let theme = args.theme();
+    let mut builder = ChartBuilder::on(&chart_area);
+    if !args.title.is_empty() {
+        builder.caption(
+            &args.title,
+            (&*args.font, 5.percent_height(), &theme.text.0),
+        );
+    }
+    builder
+        .set_label_area_size(LabelAreaPosition::Left, 7.percent_width())
+        .set_label_area_size(LabelAreaPosition::Bottom, 8.percent_height())
+        .margin(1.percent());
+
+    // Make room for the trace labels on the right of the chart, if requested
+    if args.label_lines {
+        let style = line_label_style(args, root);
+        let mut max_width = 0;
+        for trace in traces.per_trace_data.iter() {
+            let (width, _) = chart_area
+                .estimate_text_size(&trace.name, &style)
+                .with_context(|| format!("measuring the label of trace {}", trace.name))?;
+            max_width = max_width.max(width);
+        }
+        let margin = 1.percent().in_pixels(&chart_area);
+        let gap = line_label_gap(&style) as i32;
+        builder.margin_right(margin + max_width as i32 + 2 * gap);
+    }
+
+    // Differences can be negative and coefficients of variation are best read
+    // in percent, so they are drawn on a linear axis. Textual problem sizes
+    // are displayed at the measured problem sizes, which become key points.
+    let x_key_points = uses_x_labels(args).then(|| {
+        traces
+            .x_labels
+            .keys()
+            .map(|&x| x as f64)
+            .filter(|x| x_range.contains(x) || *x == x_range.end)
+            .collect::<Vec<_>>()
+    });
+    let x_coord = x_range.log_scale();
+    match (x_key_points, is_linear) {
+        (None, true) => {
+            let chart = builder
+                .build_cartesian_2d(x_coord, y_range)
+                .context("setting up the plot's chart")?;
+            draw_line_traces(args, traces, colors, num_legend_entries, root, chart)
+        }
+        (None, false) => {
+            let chart = builder
+                .build_cartesian_2d(x_coord, y_range.log_scale())
+                .context("setting up the plot's chart")?;
+            draw_line_traces(args, traces, colors, num_legend_entries, root, chart)
+        }
+        (Some(key_points), true) => {
+            let chart = builder
+                .build_cartesian_2d(x_coord.with_key_points(key_points), y_range)
+                .context("setting up the plot's chart")?;
+            draw_line_traces(args, traces, colors, num_legend_entries, root, chart)
+        }
+        (Some(key_points), false) => {
+            let chart = builder
+                .build_cartesian_2d(x_coord.with_key_points(key_points), y_range.log_scale())
+                .context("setting up the plot's chart")?;
+            draw_line_traces(args, traces, colors, num_legend_entries, root, chart)
+        }
+    }
+}
+
+/// Draw stacked line charts of several metrics of the same traces
+///
+/// The charts share the horizontal range of the traces, and only the top one
+/// is titled.
+fn draw_panels<DB: DrawingBackend>(
+    args: &Args,
+    traces: Traces,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let areas = root.split_evenly((args.panels.len(), 1));
+    for (idx, (&metric, area)) in args.panels.iter().zip(&areas).enumerate() {
+        let panel_args = Args {
+            title: if idx == 0 {
+                args.title.clone()
+            } else {
+                "".into()
+            },
+            panels: Vec::new(),
+            ..args.clone()
+        };
+        let mut traces = traces.clone();
+        traces.to_metric(metric);
+        draw_lines(&panel_args, traces, area)
+            .with_context(|| format!("drawing the {metric:?} panel"))?;
+    }
+    Ok(())
+}
+
+/// Draw line or scatter traces into a chart whose horizontal axis is
+/// logarithmic
+fn draw_line_traces<'chart, DB: DrawingBackend + 'chart, X, Y>(
+    args: &Args,
+    traces: Traces,
+    colors: Vec<RGBColor>,
+    num_legend_entries: usize,
+    root: &DrawingArea<DB, Shift>,
+    mut chart: ChartContext<'chart, DB, Cartesian2d<X, Y>>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+    X: Ranged<ValueType = f64> + ValueFormatter<f64>,
+    Y: Ranged<ValueType = f32> + ValueFormatter<f32>,
+{
+    // Set up the mesh
+    let theme = args.theme();
+    let baseline_label = args.baseline_label().unwrap_or_default();
+    chart
+        .configure_mesh()
+        .x_desc(args.x_label(&traces))
+        .x_label_formatter(&|coord| {
+            let label = uses_x_labels(args)
+                .then(|| traces.x_labels.get(&(coord.round() as usize)))
+                .flatten();
+            match label {
+                Some(label) => label.to_string(),
+                None => format!("10^{}", coord.log10().floor() as i32),
+            }
+        })
+        .y_desc(value_axis_desc(args, &traces))
+        .y_label_formatter(&|value| value_label(args, &traces, value))
+        .label_style((&*args.font, 3.percent_height(), &theme.text.0))
+        .axis_style(theme.axis.0)
+        .bold_line_style(theme.bold_mesh.0)
+        .light_line_style(theme.light_mesh.0)
+        .draw()
+        .context("setting up the plot's mesh")?;
+    draw_spines(args, &chart)?;
+
+    // Shade the performance tiers, if any, behind everything else
+    let (x_range, y_range) = (chart.x_range(), chart.y_range());
+    let (start, end) = (x_range.start, x_range.end);
+    let mut tiers = args.tier.clone();
+    tiers.sort_by(|tier1, tier2| tier1.start.total_cmp(&tier2.start));
+    for (idx, tier) in tiers.iter().enumerate() {
+        let bottom = tier.start.max(y_range.start);
+        let top = tiers
+            .get(idx + 1)
+            .map_or(y_range.end, |next| next.start.min(y_range.end));
+        if bottom >= top {
+            continue;
+        }
+        chart
+            .draw_series(std::iter::once(Rectangle::new(
+                [(start, bottom), (end, top)],
+                tier.color.0.mix(0.15).filled(),
+            )))
+            .with_context(|| format!("drawing the performance tier starting at {}", tier.start))?;
+    }
+
+    // Shade the target performance window, if any, above the tiers
+    if let Some(target) = args.target_band {
+        let bottom = target.lower.max(y_range.start);
+        let top = target.upper.min(y_range.end);
+        if bottom < top {
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [(start, bottom), (end, top)],
+                    theme.target_region.0.filled(),
+                )))
+                .context("shading the target band")?;
+        }
+    }
+
+    // When plotting differences, shade the region where traces are slower
+    // than the reference and draw the zero line
+    if let Some(Comparison::Difference(_)) = traces.comparison {
+        let y_range = chart.y_range();
+        let slower = if traces.higher_is_better() {
+            y_range.start..0.0f32.max(y_range.start)
+        } else {
+            0.0f32.min(y_range.end)..y_range.end
+        };
+        chart
+            .draw_series(std::iter::once(Rectangle::new(
+                [(start, slower.start), (end, slower.end)],
+                theme.slower_region.0.filled(),
+            )))
+            .context("shading the slower region")?;
+        chart
+            .draw_series(LineSeries::new(
+                [(start, 0.0), (end, 0.0)],
+                theme.reference_line.0,
+            ))
+            .context("drawing the zero line")?;
+    }
+
+    // When plotting ratios, draw the line where traces match the reference
+    if let Some(Comparison::Ratio(_)) = traces.comparison {
+        chart
+            .draw_series(LineSeries::new(
+                [(start, 1.0), (end, 1.0)],
+                theme.reference_line.0,
+            ))
+            .context("drawing the reference line")?;
+    }
+
+    // In scatter plots, the point with the widest confidence interval gets
+    // the largest radius and the radius of other points is proportional
+    let (_, height) = root.dim_in_pixel();
+    let (min_radius, max_radius) = (0.005 * height as f32, 0.04 * height as f32);
+    let max_relative_width = traces
+        .per_trace_data
+        .iter()
+        .flat_map(|trace| trace.data.iter())
+        .map(|(_, meas)| meas.relative_ci_width())
+        .fold(0.0, f32::max);
+    let sized_point_radius = |meas: &MeasurementDisplay| {
+        let relative_width = if max_relative_width > 0.0 {
+            meas.relative_ci_width() / max_relative_width
+        } else {
+            0.0
+        };
+        min_radius + (max_radius - min_radius) * relative_width
+    };
+
+    // Locate the crossovers between two traces, if requested
+    let crossovers = match &args.crossover[..] {
+        [] => Vec::new(),
+        [first, second] => {
+            let find_trace = |name: &str| {
+                traces
+                    .per_trace_data
+                    .iter()
+                    .find(|trace| &*trace.name == name)
+                    .with_context(|| format!("trace {name} of --crossover is not plotted"))
+            };
+            let crossovers = find_trace(first)?.crossovers(find_trace(second)?);
+            if crossovers.is_empty() {
+                args.warn(format_args!("traces {first} and {second} do not cross"))?;
+            }
+            crossovers
+                .into_iter()
+                .map(|x| {
+                    (
+                        x,
+                        format!(
+                            " {first} = {second} at {}",
+                            format::number(x as f32, args.precision, 0)
+                        ),
+                    )
+                })
+                .collect()
+        }
+        _ => bail!("--crossover expects exactly two trace names"),
+    };
+
+    // Sample the theoretical model regularly on the logarithmic horizontal
+    // axis, skipping values that cannot be drawn
+    let model_points = args.model.as_ref().map(|model| {
+        const NUM_SAMPLES: usize = 200;
+        let x_range = chart.x_range();
+        let (log_start, log_end) = (x_range.start.ln(), x_range.end.ln());
+        let is_linear = is_linear(&traces);
+        let points = (0..=NUM_SAMPLES)
+            .filter_map(|idx| {
+                let log_x = log_start + (log_end - log_start) * idx as f64 / NUM_SAMPLES as f64;
+                let x = log_x.exp();
+                let y = model.eval(x) as f32;
+                (y.is_finite() && (is_linear || y > 0.0)).then_some((x, y))
+            })
+            .collect::<Vec<_>>();
+        (model, points)
+    });
+
+    // Compute the horizontal scale factor used to jitter each trace's markers
+    let num_traces = traces.len();
+    let jitter = args.jitter.unwrap_or(0.0);
+    let log_width = x_range.end.ln() - x_range.start.ln();
+    let jitter_factor = |idx: usize| {
+        let offset = if num_traces > 1 {
+            idx as f64 / (num_traces - 1) as f64 - 0.5
+        } else {
+            0.0
+        };
+        (jitter * offset * log_width).exp()
+    };
+
+    // Draw the traces, recording where they end if they should be labeled
+    let mut line_labels = Vec::new();
+    for (idx, (trace, color)) in traces
+        .per_trace_data
+        .into_vec()
+        .into_iter()
+        .zip(colors)
+        .enumerate()
+    {
+        let jitter_factor = jitter_factor(idx);
+        // Draw the points of scatter plots, which encode the confidence
+        // interval so they need no error bars
+        if args.kind() == PlotKind::ScatterSized {
+            let style = color.mix(0.5).filled();
+            chart
+                .draw_series(trace.data.iter().map(|(x, meas)| {
+                    Circle::new(
+                        (*x as f64 * jitter_factor, meas.point_estimate),
+                        sized_point_radius(meas),
+                        style,
+                    )
+                }))
+                .with_context(|| format!("drawing trace {}", trace.name))?
+                .label(&*trace.name)
+                .legend(move |(x, y)| Circle::new((x + 10, y), 5, style));
+        } else {
+            draw_line_trace(args, &mut chart, &trace, color, jitter_factor, root)?;
+        }
+        if let Some((x, meas)) = trace.data.last().filter(|_| args.label_lines) {
+            let y = if args.kind() == PlotKind::ScatterSized {
+                meas.point_estimate
+            } else {
+                args.line_source.value(meas)
+            };
+            let end = chart.backend_coord(&(*x as f64, y));
+            line_labels.push((trace.name.clone(), color, end));
+        }
+
+        // Draw the baseline as a dashed line, if any
+        if let Some(baseline) = &trace.baseline {
+            draw_dashed_line(
+                &chart,
+                baseline
+                    .iter()
+                    .map(|(x, meas)| (*x as f64, args.line_source.value(meas))),
+                color,
+            )
+            .with_context(|| format!("drawing baseline of trace {}", trace.name))?;
+            chart
+                .draw_series(std::iter::empty::<PathElement<(f64, f32)>>())?
+                .label(format!("{} ({baseline_label})", trace.name))
+                .legend(move |(x, y)| {
+                    EmptyElement::at((x, y))
+                        + PathElement::new(vec![(0, 0), (8, 0)], color)
+                        + PathElement::new(vec![(12, 0), (20, 0)], color)
+                });
+        }
+    }
+
+    // Draw the theoretical model, if any
+    if let Some((model, points)) = model_points {
+        let color = theme.reference_line.opaque();
+        chart
+            .draw_series(LineSeries::new(points, color))
+            .context("drawing the theoretical model")?
+            .label(format!("Model: {model}"))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    // Mark the crossovers with a labeled vertical line
+    let font_size = 0.025 * root.dim_in_pixel().1 as f64;
+    let label_style = TextStyle::from((&*args.font, font_size).into_font()).color(&theme.text.0);
+    for (x, label) in crossovers {
+        draw_dashed_line(
+            &chart,
+            [(x, y_range.start), (x, y_range.end)],
+            theme.reference_line.opaque(),
+        )
+        .context("drawing a crossover line")?;
+        chart
+            .draw_series(std::iter::once(Text::new(
+                label,
+                (x, y_range.end),
+                label_style.clone(),
+            )))
+            .context("labeling a crossover line")?;
+    }
+
+    // Draw faint gridlines again on top of the traces, if requested. The
+    // labels and axes were already drawn with the rest of the mesh.
+    if args.gridlines == Gridlines::Front {
+        chart
+            .configure_mesh()
+            .disable_axes()
+            .x_label_formatter(&|_| String::new())
+            .y_label_formatter(&|_| String::new())
+            .bold_line_style(theme.bold_mesh.0.mix(0.5))
+            .light_line_style(theme.light_mesh.0.mix(0.5))
+            .draw()
+            .context("drawing the gridlines over the traces")?;
+    }
+
+    // Label the traces next to their last data point, if requested
+    if args.label_lines {
+        let (_, y_pixels) = chart.plotting_area().get_pixel_range();
+        draw_line_labels(args, root, y_pixels, line_labels)?;
+    }
+
+    // Draw the legend, unless it was drawn outside of the chart or disabled
+    if args.legend_outside.is_none() && !args.no_legend {
+        chart
+            .configure_series_labels()
+            .border_style(theme.legend_border.0)
+            .background_style(theme.legend_background.0.filled())
+            .position(SeriesLabelPosition::LowerRight)
+            .label_font({
+                let ideal_size_percent = 2.8f64;
+                let max_size_percent = 50.0 / num_legend_entries as f64;
+                (
+                    &*args.font,
+                    (ideal_size_percent.min(max_size_percent)).percent_height(),
+                    &theme.text.0,
+                )
+            })
+            .draw()
+            .context("drawing the legend")?;
+    }
+    Ok(())
+}
+
+/// Text style of the labels of `--label-lines`, before they get their color
+fn line_label_style<'args, DB: DrawingBackend>(
+    args: &'args Args,
+    root: &DrawingArea<DB, Shift>,
+) -> TextStyle<'args> {
+    let font_size = 0.025 * root.dim_in_pixel().1 as f64;
+    TextStyle::from((&*args.font, font_size).into_font()).pos(Pos::new(HPos::Left, VPos::Center))
+}
+
+/// Horizontal gap between the end of a trace and its `--label-lines` label
+fn line_label_gap(style: &TextStyle<'_>) -> u32 {
+    (0.5 * style.font.get_size()) as u32
+}
+
+/// Draw the name of each trace next to the backend coordinate where it ends
+///
+/// Labels are sorted from top to bottom, then pushed down as needed so that
+/// they are at least a line apart, and back up if this pushes some of them
+/// below the chart, whose vertical pixel range is `y_pixels`.
+fn draw_line_labels<DB: DrawingBackend>(
+    args: &Args,
+    root: &DrawingArea<DB, Shift>,
+    y_pixels: Range<i32>,
+    mut labels: Vec<(Box<str>, RGBColor, BackendCoord)>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Spread the labels vertically
+    let style = line_label_style(args, root);
+    let line_height = (1.2 * style.font.get_size()) as i32;
+    labels.sort_by_key(|(_, _, (_, y))| *y);
+    let mut label_ys = labels
+        .iter()
+        .map(|(_, _, (_, y))| (*y).clamp(y_pixels.start, y_pixels.end))
+        .collect::<Vec<_>>();
+    for idx in 1..label_ys.len() {
+        label_ys[idx] = label_ys[idx].max(label_ys[idx - 1] + line_height);
+    }
+    let mut max_y = y_pixels.end;
+    for label_y in label_ys.iter_mut().rev() {
+        *label_y = (*label_y).min(max_y);
+        max_y = *label_y - line_height;
+    }
+
+    // Draw them on the right of the end of their trace
+    let gap = line_label_gap(&style) as i32;
+    let (base_x, base_y) = root.get_base_pixel();
+    for ((name, color, (end_x, _)), label_y) in labels.into_iter().zip(label_ys) {
+        root.draw(&Text::new(
+            name.to_string(),
+            (end_x + gap - base_x, label_y - base_y),
+            style.color(&color),
+        ))
+        .with_context(|| format!("labeling trace {name}"))?;
+    }
+    Ok(())
+}
+
+/// Draw a trace of a line chart, along with its error bars
+///
+/// Error bars are drawn at problem sizes multiplied by `jitter_factor`.
+fn draw_line_trace<'chart, DB: DrawingBackend + 'chart, X, Y>(
+    args: &Args,
+    chart: &mut ChartContext<'chart, DB, Cartesian2d<X, Y>>,
+    trace: &Trace,
+    color: RGBColor,
+    jitter_factor: f64,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+    X: Ranged<ValueType = f64> + ValueFormatter<f64>,
+    Y: Ranged<ValueType = f32> + ValueFormatter<f32>,
+{
+    // Draw the trace
+    chart
+        .draw_series(LineSeries::new(
+            trace
+                .data
+                .iter()
+                .map(|(x, meas)| (*x as f64, args.line_source.value(meas))),
+            color,
+        ))
+        .with_context(|| format!("drawing trace {}", trace.name))?
+        .label(&*trace.name)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+
+    // Draw the confidence intervals, which are skipped in previews for speed
+    if args.preview {
+        return Ok(());
+    }
+    if args.error_style == ErrorStyle::Band {
+        // Go along the upper bounds, then back along the lower bounds, so that
+        // the polygon does not cross itself
+        let upper = trace
+            .data
+            .iter()
+            .map(|(x, meas)| (*x as f64, meas.upper_bound));
+        let lower = trace
+            .data
+            .iter()
+            .rev()
+            .map(|(x, meas)| (*x as f64, meas.lower_bound));
+        chart
+            .draw_series(std::iter::once(Polygon::new(
+                upper.chain(lower).collect::<Vec<_>>(),
+                color.mix(0.2).filled(),
+            )))
+            .with_context(|| format!("drawing the confidence band of trace {}", trace.name))?;
+        return Ok(());
+    }
+    chart.draw_series(trace.data.iter().map(|(x, meas)| {
+        ErrorBar::new_vertical(
+            *x as f64 * jitter_factor,
+            meas.lower_bound,
+            meas.point_estimate,
+            meas.upper_bound,
+            color,
+            error_bar_size(root),
+        )
+    }))?;
+    Ok(())
+}
+
+/// Draw the relative width of confidence intervals as a faint track
+///
+/// The track is meant to be drawn below a line chart with the same horizontal
+/// range, label area and left/right margins, so that problem sizes line up.
+fn draw_ci_track<DB: DrawingBackend>(
+    args: &Args,
+    traces: &Traces,
+    colors: &[RGBColor],
+    x_range: Range<f64>,
+    margin: i32,
+    area: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Compute the relative confidence interval widths
+    let widths = traces
+        .per_trace_data
+        .iter()
+        .map(|trace| {
+            trace
+                .data
+                .iter()
+                .map(|(x, meas)| (*x as f64, 100.0 * meas.relative_ci_width()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let max_width = widths
+        .iter()
+        .flatten()
+        .map(|(_, width)| *width)
+        .fold(0.0f32, f32::max);
+
+    // Set up the track
+    let theme = args.theme();
+    let mut chart = ChartBuilder::on(area)
+        .set_label_area_size(LabelAreaPosition::Left, 7.percent_width())
+        .margin_left(margin)
+        .margin_right(margin)
+        .margin_bottom(margin)
+        .build_cartesian_2d(x_range.log_scale(), 0.0..1.1 * max_width.max(f32::EPSILON))
+        .context("setting up the confidence interval track")?;
+    chart
+        .configure_mesh()
+        .y_desc("CI width")
+        .y_labels(3)
+        .y_label_formatter(&|width| format!("{}%", format::number(*width, args.precision, 1)))
+        .label_style((&*args.font, 12.percent_height(), &theme.text.0))
+        .axis_style(theme.axis.0)
+        .bold_line_style(theme.bold_mesh.0)
+        .light_line_style(theme.light_mesh.0)
+        .draw()
+        .context("setting up the confidence interval track's mesh")?;
+
+    // Draw the relative confidence interval widths of each trace
+    for (widths, color) in widths.into_iter().zip(colors) {
+        chart
+            .draw_series(LineSeries::new(widths, color.mix(0.5)))
+            .context("drawing a confidence interval track")?;
+    }
+    Ok(())
+}
+
+/// Draw the legend in a strip at the edge of the drawing area
+///
+/// Returns the remaining drawing area, where the chart should be drawn.
+fn draw_outside_legend<DB: DrawingBackend>(
+    args: &Args,
+    area: &DrawingArea<DB, Shift>,
+    position: LegendOutside,
+    entries: &[LegendEntry<'_>],
+) -> Result<DrawingArea<DB, Shift>>
+where
+    DB::ErrorType: 'static,
+{
+    // Pick the legend font size, with the same rules as the inner legend
+    let (width, height) = area.dim_in_pixel();
+    let num_entries = entries.len();
+    let font_size = (0.028f64).min(0.5 / num_entries as f64) * height as f64;
+    let theme = args.theme();
+    let text_style = TextStyle::from((&*args.font, font_size).into_font())
+        .color(&theme.text.0)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+
+    // Determine the legend layout
+    const SWATCH_WIDTH: i32 = 20;
+    let margin = (font_size / 2.0) as i32;
+    let row_height = (1.5 * font_size) as i32;
+    let mut max_label_width = 0;
+    for entry in entries {
+        let (label_width, _) = area
+            .estimate_text_size(&entry.label, &text_style)
+            .context("measuring legend labels")?;
+        max_label_width = max_label_width.max(label_width as i32);
+    }
+    let column_width = SWATCH_WIDTH + margin + max_label_width + 2 * margin;
+    let num_columns = match position {
+        LegendOutside::Right => 1,
+        LegendOutside::Bottom => {
+            ((width as i32 - margin) / column_width).clamp(1, num_entries as i32) as usize
+        }
+    };
+    let num_rows = num_entries.div_ceil(num_columns);
+
+    // Split the drawing area accordingly
+    let (chart_area, legend_area) = match position {
+        LegendOutside::Right => {
+            let legend_width = (column_width + margin).min(width as i32 * 2 / 5);
+            area.split_horizontally(width as i32 - legend_width)
+        }
+        LegendOutside::Bottom => {
+            let legend_height = num_rows as i32 * row_height + 2 * margin;
+            area.split_vertically(height as i32 - legend_height)
+        }
+    };
+
+    // Draw the legend entries, in column-major order
+    for (idx, entry) in entries.iter().enumerate() {
+        let (column, row) = (idx / num_rows, idx % num_rows);
+        let x = margin + column as i32 * column_width;
+        let y = margin + row as i32 * row_height + row_height / 2;
+        let swatch = vec![(x, y), (x + SWATCH_WIDTH, y)];
+        let swatch = if entry.dashed {
+            dash_segments(&swatch, DASH_LENGTH, GAP_LENGTH)
+        } else {
+            vec![swatch]
+        };
+        for segment in swatch {
+            legend_area
+                .draw(&PathElement::new(segment, entry.color))
+                .context("drawing legend swatch")?;
+        }
+        legend_area
+            .draw(&Text::new(
+                &*entry.label,
+                (x + SWATCH_WIDTH + margin, y),
+                text_style.clone(),
+            ))
+            .context("drawing legend label")?;
+    }
+    Ok(chart_area)
+}
+
+/// Legend entry, for legends that are drawn manually
+struct LegendEntry<'name> {
+    /// Text of the entry
+    label: Cow<'name, str>,
+
+    /// Color of the matching trace
+    color: RGBColor,
+
+    /// Truth that the matching trace is drawn with a dashed line
+    dashed: bool,
+}
+
+/// Length of the dashes of dashed lines in pixels
+const DASH_LENGTH: f64 = 8.0;
+
+/// Length of the gaps between dashes of dashed lines in pixels
+const GAP_LENGTH: f64 = 4.0;
+
+/// Draw a dashed line through some points of a cartesian chart
+fn draw_dashed_line<DB: DrawingBackend, X: Ranged, Y: Ranged>(
+    chart: &ChartContext<'_, DB, Cartesian2d<X, Y>>,
+    points: impl IntoIterator<Item = (X::ValueType, Y::ValueType)>,
+    color: RGBColor,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let area = chart.plotting_area().strip_coord_spec();
+    let (base_x, base_y) = area.get_base_pixel();
+    let path = points
+        .into_iter()
+        .map(|point| {
+            let (x, y) = chart.backend_coord(&point);
+            (x - base_x, y - base_y)
+        })
+        .collect::<Vec<_>>();
+    for dash in dash_segments(&path, DASH_LENGTH, GAP_LENGTH) {
+        area.draw(&PathElement::new(dash, color))
+            .context("drawing a dash")?;
+    }
+    Ok(())
+}
+
+/// Split a polyline in pixel coordinates into dashes
+fn dash_segments(
+    path: &[BackendCoord],
+    dash_length: f64,
+    gap_length: f64,
+) -> Vec<Vec<BackendCoord>> {
+    let period = dash_length + gap_length;
+    let mut dashes = Vec::new();
+    let mut current_dash = Vec::new();
+    let mut phase = 0.0;
+    for segment in path.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let (dx, dy) = ((end.0 - start.0) as f64, (end.1 - start.1) as f64);
+        let length = dx.hypot(dy);
+        let point_at = |pos: f64| {
+            (
+                (start.0 as f64 + dx * pos / length).round() as i32,
+                (start.1 as f64 + dy * pos / length).round() as i32,
+            )
+        };
+        let mut pos = 0.0;
+        while pos < length {
+            let in_dash = phase < dash_length;
+            let step = if in_dash {
+                dash_length - phase
+            } else {
+                period - phase
+            }
+            .min(length - pos);
+            if in_dash {
+                if current_dash.is_empty() {
+                    current_dash.push(point_at(pos));
+                }
+                current_dash.push(point_at(pos + step));
+            }
+            pos += step;
+            phase += step;
+            if in_dash && phase >= dash_length {
+                dashes.push(std::mem::take(&mut current_dash));
+            }
+            if phase >= period {
+                phase -= period;
+            }
+        }
     }
-    if let Some(max_y) = args.max_y {
-        y_range.end = max_y;
-    } else {
-        y_range.end *= 2.0;
+    if current_dash.len() >= 2 {
+        dashes.push(current_dash);
     }
+    dashes
+}
+
+/// Draw a caterpillar plot, with one row per single-point trace
+fn draw_caterpillar<DB: DrawingBackend>(
+    args: &Args,
+    traces: Traces,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Differences can be negative, which the logarithmic axis cannot display
+    ensure!(
+        !matches!(traces.comparison, Some(Comparison::Difference(_))),
+        "caterpillar plots of differences are not supported"
+    );
+    ensure!(
+        args.model.is_none(),
+        "theoretical models cannot be drawn on caterpillar plots"
+    );
+
+    // Pick one data point per trace and assign colors in trace order, then
+    // sort rows by point estimate
+    let num_traces = traces.len();
+    let mut rows = traces
+        .single_points(args.problem_size)?
+        .into_iter()
+        .zip(trace_colors(args, &traces))
+        .map(|((name, meas), color)| (name, meas, color))
+        .collect::<Vec<(&str, MeasurementDisplay, RGBColor)>>();
+    rows.sort_by(|(_, meas1, _), (_, meas2, _)| {
+        meas1.point_estimate.total_cmp(&meas2.point_estimate)
+    });
 
     // Set up the chart
-    let mut chart = ChartBuilder::on(&root);
+    let x_range = value_range(
+        args,
+        rows.iter()
+            .map(|(_, meas, _)| meas.lower_bound)
+            .min_by(f32::total_cmp)
+            .expect("there should be >= 1 trace")
+            ..rows
+                .iter()
+                .map(|(_, meas, _)| meas.upper_bound)
+                .max_by(f32::total_cmp)
+                .expect("there should be >= 1 trace"),
+    );
+    let theme = args.theme();
+    let mut chart = ChartBuilder::on(root);
     if !args.title.is_empty() {
-        chart.caption(&args.title, ("sans-serif", 5.percent_height()));
+        chart.caption(
+            &args.title,
+            (&*args.font, 5.percent_height(), &theme.text.0),
+        );
     }
     let mut chart = chart
-        .set_label_area_size(LabelAreaPosition::Left, 7.percent_width())
+        .set_label_area_size(LabelAreaPosition::Left, 20.percent_width())
         .set_label_area_size(LabelAreaPosition::Bottom, 8.percent_height())
         .margin(1.percent())
-        .build_cartesian_2d(x_range.log_scale(), y_range.log_scale())
+        .build_cartesian_2d(
+            x_range.log_scale(),
+            // Plotters integer ranges are inclusive
+            (0..num_traces as i32 - 1).into_segmented(),
+        )
         .context("setting up the plot's chart")?;
 
-    // Set up the mesh
+    // Set up the mesh, using trace names as vertical axis labels
     chart
         .configure_mesh()
-        .x_desc(args.x_label.to_string())
-        .x_label_formatter(&|coord| format!("10^{}", coord.log10().floor() as i32))
-        .y_desc(match traces.throughput {
-            None => "Time (s)".to_string(),
-            Some(ThroughputType::Bytes) | Some(ThroughputType::BytesDecimal) => {
-                "Bandwidth (B/s)".to_string()
-            }
-            Some(ThroughputType::Elements) => {
-                format!("Throughput ({}/s)", args.element_throughput_unit)
-            }
+        .disable_y_mesh()
+        .x_desc(value_axis_desc(args, &traces))
+        .x_label_formatter(&|value| value_label(args, &traces, value))
+        .y_labels(num_traces)
+        .y_label_formatter(&|segment| match segment {
+            SegmentValue::CenterOf(idx) => usize::try_from(*idx)
+                .ok()
+                .and_then(|idx| rows.get(idx))
+                .map_or_else(String::new, |(name, _, _)| name.to_string()),
+            _ => String::new(),
         })
-        .y_label_formatter(&axis_label_si)
-        .label_style(("sans-serif", 3.percent_height()))
+        .label_style((&*args.font, 3.percent_height(), &theme.text.0))
+        .axis_style(theme.axis.0)
+        .bold_line_style(theme.bold_mesh.0)
+        .light_line_style(theme.light_mesh.0)
         .draw()
         .context("setting up the plot's mesh")?;
+    draw_spines(args, &chart)?;
 
-    // Draw the traces
-    let num_traces = traces.len();
-    let color_pos_norm = 1.0 / num_traces as f64;
-    for (idx, trace) in traces.per_trace_data.into_vec().into_iter().enumerate() {
-        // Pick the trace color
-        let color_pos = idx as f64 * color_pos_norm;
-        let color = SINEBOW.eval_continuous(color_pos);
-        let color = RGBColor(color.r, color.g, color.b);
-
-        // Draw the trace
+    // Draw the confidence intervals and point estimates
+    let bar_height = error_bar_size(root);
+    for (idx, (name, meas, color)) in rows.iter().enumerate() {
+        let row = SegmentValue::CenterOf(idx as i32);
         chart
-            .draw_series(LineSeries::new(
-                trace
-                    .data
-                    .iter()
-                    .map(|(x, meas)| (*x as f64, meas.point_estimate)),
-                color,
-            ))
-            .with_context(|| format!("drawing trace {}", trace.name))?
-            .label(trace.name)
-            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
-
-        // Draw the error bars
-        chart.draw_series(trace.data.iter().map(|(x, meas)| {
-            ErrorBar::new_vertical(
-                *x as f64,
+            .draw_series(std::iter::once(ErrorBar::new_horizontal(
+                row.clone(),
                 meas.lower_bound,
                 meas.point_estimate,
                 meas.upper_bound,
-                color,
-                (0.01 * args.height.get() as f32) as u32,
-            )
-        }))?;
+                *color,
+                bar_height,
+            )))
+            .with_context(|| format!("drawing confidence interval of {name}"))?;
+        chart
+            .draw_series(std::iter::once(Circle::new(
+                (meas.point_estimate, row),
+                bar_height / 2 + 1,
+                color.filled(),
+            )))
+            .with_context(|| format!("drawing point estimate of {name}"))?;
     }
+    Ok(())
+}
+
+/// Draw a heatmap of the pairwise speedups between single-point traces
+fn draw_speedup_matrix<DB: DrawingBackend>(
+    args: &Args,
+    traces: Traces,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Ratios of differences are meaningless
+    ensure!(
+        !matches!(traces.comparison, Some(Comparison::Difference(_))),
+        "speedup matrices of differences are not supported"
+    );
+    ensure!(
+        args.model.is_none(),
+        "theoretical models cannot be drawn on speedup matrices"
+    );
+
+    // Compute the speedup of each trace over each other trace, which is
+    // the inverse of the ratio of execution times
+    let points = traces.single_points(args.problem_size)?;
+    let num_traces = points.len();
+    let speedup = |row: usize, column: usize| {
+        let ratio = points[row].1.point_estimate / points[column].1.point_estimate;
+        if traces.higher_is_better() {
+            ratio
+        } else {
+            ratio.recip()
+        }
+    };
+    let max_log_speedup = (0..num_traces)
+        .flat_map(|row| (0..num_traces).map(move |column| (row, column)))
+        .map(|(row, column)| speedup(row, column).log2().abs())
+        .filter(|log_speedup| log_speedup.is_finite())
+        .fold(0.0f32, f32::max);
 
-    // Draw the legend
+    // Set up the chart, with the first trace on the top row
+    let theme = args.theme();
+    let mut chart = ChartBuilder::on(root);
+    if !args.title.is_empty() {
+        chart.caption(
+            &args.title,
+            (&*args.font, 5.percent_height(), &theme.text.0),
+        );
+    }
+    let mut chart = chart
+        .set_label_area_size(LabelAreaPosition::Left, 20.percent_width())
+        .set_label_area_size(LabelAreaPosition::Bottom, 8.percent_height())
+        .margin(1.percent())
+        .build_cartesian_2d(
+            // Plotters integer ranges are inclusive
+            (0..num_traces as i32 - 1).into_segmented(),
+            (0..num_traces as i32 - 1).into_segmented(),
+        )
+        .context("setting up the plot's chart")?;
+    let trace_name = |segment: &SegmentValue<i32>, flip: bool| match segment {
+        SegmentValue::CenterOf(idx) => usize::try_from(*idx)
+            .ok()
+            .filter(|idx| *idx < num_traces)
+            .map(|idx| if flip { num_traces - 1 - idx } else { idx })
+            .map_or_else(String::new, |idx| points[idx].0.to_string()),
+        _ => String::new(),
+    };
     chart
-        .configure_series_labels()
-        .border_style(BLACK)
-        .background_style(WHITE.filled())
-        .position(SeriesLabelPosition::LowerRight)
-        .label_font({
-            let ideal_size_percent = 2.8f64;
-            let max_size_percent = 50.0 / num_traces as f64;
-            (
-                "sans-serif",
-                (ideal_size_percent.min(max_size_percent)).percent_height(),
-            )
-        })
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Reference trace")
+        .y_desc("Trace")
+        .x_labels(num_traces)
+        .y_labels(num_traces)
+        .x_label_formatter(&|segment| trace_name(segment, false))
+        .y_label_formatter(&|segment| trace_name(segment, true))
+        .label_style((&*args.font, 3.percent_height(), &theme.text.0))
+        .axis_style(theme.axis.0)
         .draw()
-        .context("drawing the legend")?;
+        .context("setting up the plot's mesh")?;
+    draw_spines(args, &chart)?;
+
+    // Draw the cells, shading speedups in green and slowdowns in red
+    let (_, plot_height) = chart.plotting_area().dim_in_pixel();
+    let font_size =
+        (0.3 * plot_height as f64 / num_traces as f64).min(0.03 * root.dim_in_pixel().1 as f64);
+    let label_style = TextStyle::from((&*args.font, font_size).into_font())
+        .color(&theme.text.0)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    for row in 0..num_traces {
+        let y = (num_traces - 1 - row) as i32;
+        for column in 0..num_traces {
+            let x = column as i32;
+            let speedup = speedup(row, column);
+            let intensity = if max_log_speedup > 0.0 {
+                f64::from(speedup.log2().abs() / max_log_speedup)
+            } else {
+                0.0
+            };
+            let base = if speedup >= 1.0 {
+                theme.faster_cell.opaque()
+            } else {
+                theme.slower_cell.opaque()
+            };
+            let mix = |component: u8| {
+                (255.0 + (f64::from(component) - 255.0) * 0.6 * intensity).round() as u8
+            };
+            let cell_color = RGBColor(mix(base.0), mix(base.1), mix(base.2));
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [
+                        (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                        (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1)),
+                    ],
+                    cell_color.filled(),
+                )))
+                .context("drawing a speedup matrix cell")?;
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    format!("{}x", format::number(speedup, args.precision, 2)),
+                    (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)),
+                    label_style.clone(),
+                )))
+                .context("labeling a speedup matrix cell")?;
+        }
+    }
+    Ok(())
+}
+
+/// Draw the top and right borders of a chart, if requested
+///
+/// Plotters only draws the left and bottom axes along with the mesh.
+fn draw_spines<DB: DrawingBackend, CT: CoordTranslate>(
+    args: &Args,
+    chart: &ChartContext<'_, DB, CT>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    if args.spines != Spines::Box {
+        return Ok(());
+    }
+    let area = chart.plotting_area().strip_coord_spec();
+    let (width, height) = area.dim_in_pixel();
+    let (right, bottom) = (width as i32 - 1, height as i32 - 1);
+    area.draw(&PathElement::new(
+        [(0, 0), (right, 0), (right, bottom)],
+        args.theme().axis.0,
+    ))
+    .context("drawing the chart's borders")
+}
+
+/// Size of the error bar whiskers in pixels
+fn error_bar_size<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>) -> u32 {
+    (0.01 * area.dim_in_pixel().1 as f32) as u32
+}
+
+/// Horizontal and vertical range of line charts and scatter plots
+pub fn line_chart_ranges(args: &Args, traces: &Traces) -> (Range<f64>, Range<f32>) {
+    let (mut x_range, y_range) = traces.xy_range();
+    if !args.track.is_empty() {
+        // Leave room for the labels of the first and last snapshots, which
+        // are one power of two apart
+        x_range = x_range.start / 2.0f64.sqrt()..x_range.end * 2.0f64.sqrt();
+    }
+    let y_range = if is_linear(traces) {
+        linear_range(args, y_range)
+    } else {
+        value_range(args, y_range)
+    };
+    (x_range, y_range)
+}
+
+/// Pad the range of measured values for display, honoring user overrides
+fn value_range(args: &Args, mut range: Range<f32>) -> Range<f32> {
+    if let Some(min_y) = args.min_y {
+        range.start = min_y;
+    } else {
+        range.start *= 0.5;
+    }
+    if let Some(max_y) = args.max_y {
+        range.end = max_y;
+    } else {
+        range.end *= 2.0;
+    }
+    range
+}
+
+/// Pad the range of values for display on a linear axis, honoring user
+/// overrides
+///
+/// Unlike [`value_range()`], the range always includes the zero line.
+fn linear_range(args: &Args, range: Range<f32>) -> Range<f32> {
+    let range = range.start.min(0.0)..range.end.max(0.0);
+    let padding = 0.05 * (range.end - range.start);
+    let start = args.min_y.unwrap_or(range.start - padding);
+    let end = args.max_y.unwrap_or(range.end + padding);
+    start..end
+}
+
+/// Truth that the horizontal axis is labeled with Criterion's textual
+/// descriptions of problem sizes, or with the labels of tracked snapshots
+fn uses_x_labels(args: &Args) -> bool {
+    args.x_tick_labels == XTickLabels::ValueStr || !args.track.is_empty()
+}
+
+/// Truth that the values of these traces are drawn on a linear axis
+fn is_linear(traces: &Traces) -> bool {
+    matches!(traces.comparison, Some(Comparison::Difference(_)))
+        || traces.metric != Metric::Measurement
+}
+
+/// Description of the measured value's axis
+fn value_axis_desc(args: &Args, traces: &Traces) -> String {
+    match traces.metric {
+        Metric::Measurement => {}
+        Metric::Cv => return "Coefficient of variation (%)".to_string(),
+        Metric::CiWidth => return "Confidence interval width (%)".to_string(),
+        Metric::MeanMedianRatio => return "Mean / median execution time".to_string(),
+    }
+    let (quantity, unit) = match traces.throughput {
+        None => ("Time", "s".to_string()),
+        Some(ThroughputType::Bytes) | Some(ThroughputType::BytesDecimal) => {
+            ("Bandwidth", format!("B{}", args.throughput_suffix))
+        }
+        Some(ThroughputType::Elements) => (
+            "Throughput",
+            format!("{}{}", args.element_throughput_unit, args.throughput_suffix),
+        ),
+    };
+    match &traces.comparison {
+        Some(Comparison::Difference(reference)) => {
+            format!("{quantity} difference with {reference} ({unit})")
+        }
+        Some(Comparison::Ratio(reference)) => format!("{quantity} relative to {reference}"),
+        None => format!("{quantity} ({unit})"),
+    }
+}
+
+/// Pick the color of each trace
+///
+/// By default, each trace gets a base color based on its position in the
+/// trace list, which comes after the names of [`Args::color_names`]. With
+/// `--color-by`, traces are grouped into families instead, each family gets a
+/// base color, and the lightness of this base color is varied across the
+/// traces of a family.
+fn trace_colors(args: &Args, traces: &Traces) -> Vec<RGBColor> {
+    let named_colors = color_key(args, traces.per_trace_data.iter().map(|trace| &*trace.name));
+    traces
+        .per_trace_data
+        .iter()
+        .map(|trace| {
+            named_colors
+                .iter()
+                .find(|(name, _)| *name == &*trace.name)
+                .expect("every trace should have a color")
+                .1
+        })
+        .collect()
+}
+
+/// Pick the colors of [`Args::color_names`], followed by other trace names
+///
+/// See [`trace_colors()`] for the color assignment rules.
+pub fn color_key<'names>(
+    args: &'names Args,
+    trace_names: impl IntoIterator<Item = &'names str>,
+) -> Vec<(&'names str, RGBColor)> {
+    // List the names that get a color, starting with those that must get
+    // consistent colors across plots
+    let mut names = args
+        .color_names
+        .iter()
+        .map(|name| &**name)
+        .collect::<Vec<_>>();
+    for name in trace_names {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    // Assign each name to a family, or to its own family by default
+    let mut families = Vec::<&str>::new();
+    let mut family_members = Vec::<Vec<usize>>::new();
+    for (idx, name) in names.iter().enumerate() {
+        let family = args.color_by.as_ref().and_then(|regex| {
+            let captures = regex.captures(name)?;
+            Some(captures.get(1).or(captures.get(0))?.as_str())
+        });
+        let family_idx = match family.and_then(|family| families.iter().position(|f| *f == family))
+        {
+            Some(family_idx) => family_idx,
+            None => {
+                families.push(family.unwrap_or(name));
+                family_members.push(Vec::new());
+                families.len() - 1
+            }
+        };
+        family_members[family_idx].push(idx);
+    }
+
+    // Pick the base color of each family, then vary it across family members
+    let base_colors = base_colors(args, families.len());
+    let mut colors = vec![RGBColor(0, 0, 0); names.len()];
+    for (members, base_color) in family_members.into_iter().zip(base_colors) {
+        let num_members = members.len();
+        for (rank, idx) in members.into_iter().enumerate() {
+            colors[idx] = if num_members == 1 {
+                base_color
+            } else {
+                // Go from a darker to a lighter version of the base color
+                let lightness = 0.8 * (rank as f64 / (num_members - 1) as f64 - 0.5);
+                shade(base_color, lightness)
+            };
+        }
+    }
+    names.into_iter().zip(colors).collect()
+}
+
+/// Pick `num_colors` base colors based on their position in a list
+///
+/// Colors are taken from the theme's palette if it has one, or from a color
+/// gradient otherwise. If the user specified a color seed, the positions are
+/// shuffled using a platform-independent PRNG.
+fn base_colors(args: &Args, num_colors: usize) -> Vec<RGBColor> {
+    let mut positions = (0..num_colors).collect::<Vec<_>>();
+    if let Some(seed) = args.color_seed {
+        let mut rng = SplitMix64(seed);
+        for idx in (1..num_colors).rev() {
+            let other_idx = (rng.next_u64() % (idx as u64 + 1)) as usize;
+            positions.swap(idx, other_idx);
+        }
+    }
+    let palette = &args.theme().palette;
+    positions
+        .into_iter()
+        .map(|position| {
+            if !palette.is_empty() {
+                return palette[position % palette.len()].opaque();
+            }
+            let color_pos = args.color_range.remap(position as f64 / num_colors as f64);
+            let color = SINEBOW.eval_continuous(color_pos);
+            RGBColor(color.r, color.g, color.b)
+        })
+        .collect()
+}
+
+/// Darken (negative `lightness`) or lighten (positive `lightness`) a color
+///
+/// A lightness of -1 yields black and a lightness of 1 yields white.
+fn shade(color: RGBColor, lightness: f64) -> RGBColor {
+    let target = if lightness < 0.0 { 0.0 } else { 255.0 };
+    let weight = lightness.abs().min(1.0);
+    let mix =
+        |component: u8| (f64::from(component) * (1.0 - weight) + target * weight).round() as u8;
+    RGBColor(mix(color.0), mix(color.1), mix(color.2))
+}
 
-    // Manually call preset to avoid errors being silently ignored
-    root.present()
-        .context("failed to write the plot to the output file")
+/// SplitMix64 pseudo-random number generator
+///
+/// This is not a high-quality PRNG, but it is good enough for shuffling
+/// colors, and its output is fully specified so it won't change across
+/// platforms or dependency updates.
+struct SplitMix64(u64);
+//
+impl SplitMix64 {
+    /// Generate the next pseudo-random number
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 }
 
 /// Abstraction over the multiple DrawingBackends provided by plotters
 ///
 /// `dyn DrawingBackend` is not applicable here as the trait is not object-safe.
-enum DrawingBackendImpl<'path> {
+enum DrawingBackendImpl<'target> {
     /// Bitmap drawing backend
-    Bitmap(BitMapBackend<'path, RGBPixel>),
+    Bitmap(BitMapBackend<'target, RGBPixel>),
+
+    /// Bitmap drawing backend without antialiasing
+    AliasedBitmap(AliasedBitmap<'target>),
 
     /// SVG drawing backend
-    Svg(SVGBackend<'path>),
+    Svg(SVGBackend<'target>),
 }
 //
-impl<'path> DrawingBackendImpl<'path> {
-    /// Pick drawing backend based on file extension
-    pub fn new(path: &'path impl AsRef<Path>, wh: (u32, u32)) -> Result<Self> {
-        let path = path.as_ref();
-        let extension = path
-            .extension()
-            .context("need file extension to pick backend")?;
-        if extension == "svg" {
-            Ok(Self::svg(path, wh))
-        } else {
-            Ok(Self::bitmap(path, wh))
-        }
-    }
-
+impl<'target> DrawingBackendImpl<'target> {
     /// Create a bitmap drawing backend
-    pub fn bitmap(path: &'path (impl AsRef<Path> + ?Sized), wh: (u32, u32)) -> Self {
+    pub fn bitmap(path: &'target (impl AsRef<Path> + ?Sized), wh: (u32, u32)) -> Self {
         Self::Bitmap(BitMapBackend::new(path, wh))
     }
 
-    /// Create an SVG drawing backend
-    pub fn svg(path: &'path (impl AsRef<Path> + ?Sized), wh: (u32, u32)) -> Self {
-        Self::Svg(SVGBackend::new(path, wh))
+    /// Create an SVG drawing backend that renders into a string
+    pub fn svg_string(buffer: &'target mut String, wh: (u32, u32)) -> Self {
+        Self::Svg(SVGBackend::with_string(buffer, wh))
     }
 }
 //
@@ -167,6 +1878,7 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
     fn get_size(&self) -> (u32, u32) {
         match self {
             Self::Bitmap(b) => b.get_size(),
+            Self::AliasedBitmap(b) => b.get_size(),
             Self::Svg(s) => s.get_size(),
         }
     }
@@ -176,6 +1888,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .ensure_prepared()
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .ensure_prepared()
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .ensure_prepared()
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -185,6 +1900,7 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
     fn present(&mut self) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
         match self {
             Self::Bitmap(b) => b.present().map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b.present().map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s.present().map_err(AnyhowError::erase_drawing_error_kind),
         }
     }
@@ -198,6 +1914,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_pixel(point, color)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_pixel(point, color)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_pixel(point, color)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -214,6 +1933,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_line(from, to, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_line(from, to, style)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_line(from, to, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -231,6 +1953,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_rect(upper_left, bottom_right, style, fill)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_rect(upper_left, bottom_right, style, fill)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_rect(upper_left, bottom_right, style, fill)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -246,6 +1971,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_path(path, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_path(path, style)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_path(path, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -263,6 +1991,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_circle(center, radius, style, fill)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_circle(center, radius, style, fill)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_circle(center, radius, style, fill)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -278,6 +2009,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .fill_polygon(vert, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .fill_polygon(vert, style)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .fill_polygon(vert, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -294,6 +2028,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .draw_text(text, style, pos)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .draw_text(text, style, pos)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .draw_text(text, style, pos)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -309,6 +2046,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .estimate_text_size(text, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .estimate_text_size(text, style)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .estimate_text_size(text, style)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -325,6 +2065,9 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
             Self::Bitmap(b) => b
                 .blit_bitmap(pos, iwh, src)
                 .map_err(AnyhowError::erase_drawing_error_kind),
+            Self::AliasedBitmap(b) => b
+                .blit_bitmap(pos, iwh, src)
+                .map_err(AnyhowError::erase_drawing_error_kind),
             Self::Svg(s) => s
                 .blit_bitmap(pos, iwh, src)
                 .map_err(AnyhowError::erase_drawing_error_kind),
@@ -332,6 +2075,169 @@ impl DrawingBackend for DrawingBackendImpl<'_> {
     }
 }
 
+/// Bitmap drawing backend that does not antialias shapes and text
+///
+/// Plotters antialiases by drawing the edge pixels of shapes and glyphs with
+/// a fraction of their opacity that matches their coverage. Here, shapes and
+/// text are rasterized through a [`CoverageThreshold`] that draws the pixels
+/// which are mostly covered with the full opacity of the shape, and drops the
+/// others. Axis-aligned rectangles are not antialiased to begin with, and go
+/// straight to the bitmap.
+struct AliasedBitmap<'target>(BitMapBackend<'target, RGBPixel>);
+//
+impl AliasedBitmap<'_> {
+    /// Rasterize a shape or text of some color without antialiasing
+    fn threshold(&mut self, color: BackendColor) -> CoverageThreshold<'_, Self> {
+        CoverageThreshold {
+            backend: self,
+            opacity: color.alpha,
+        }
+    }
+}
+//
+impl DrawingBackend for AliasedBitmap<'_> {
+    type ErrorType = <BitMapBackend<'static, RGBPixel> as DrawingBackend>::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.0.get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.ensure_prepared()
+    }
+
+    fn present(&mut self) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.present()
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.draw_pixel(point, color)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.threshold(style.color()).draw_line(from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.draw_rect(upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.threshold(style.color()).draw_path(path, style)
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.threshold(style.color())
+            .draw_circle(center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.threshold(style.color()).fill_polygon(vert, style)
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.threshold(style.color()).draw_text(text, style, pos)
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> std::result::Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.0.estimate_text_size(text, style)
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        iwh: (u32, u32),
+        src: &[u8],
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.blit_bitmap(pos, iwh, src)
+    }
+}
+
+/// Drawing backend adapter that turns partially covered pixels into fully
+/// covered or uncovered ones
+///
+/// Only the pixel drawing primitive is implemented, so that plotters' default
+/// rasterization of shapes and text goes through it.
+struct CoverageThreshold<'backend, B: DrawingBackend> {
+    /// Backend where pixels are drawn
+    backend: &'backend mut B,
+
+    /// Opacity of the shape or text that is being rasterized
+    opacity: f64,
+}
+//
+impl<B: DrawingBackend> DrawingBackend for CoverageThreshold<'_, B> {
+    type ErrorType = B::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.backend.get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.backend.ensure_prepared()
+    }
+
+    fn present(&mut self) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.backend.present()
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> std::result::Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if color.alpha >= 0.5 * self.opacity {
+            self.backend.draw_pixel(
+                point,
+                BackendColor {
+                    alpha: self.opacity,
+                    rgb: color.rgb,
+                },
+            )
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// [`anyhow::Error`] wrapper that implements [`std::error::Error`]
 #[derive(Debug)]
 struct AnyhowError(anyhow::Error);
@@ -410,33 +2316,21 @@ impl Error for AnyhowError {
     }
 }
 
-/// Render an axis label using SI prefixes, if available
-fn axis_label_si(coord: &f32) -> String {
-    let si_power = (coord.log10() / 3.0).floor() as i32 * 3;
-    let si_prefix: Cow<str> = match si_power {
-        -30 => "q".into(),
-        -27 => "r".into(),
-        -24 => "y".into(),
-        -21 => "z".into(),
-        -18 => "a".into(),
-        -15 => "f".into(),
-        -12 => "p".into(),
-        -9 => "n".into(),
-        -6 => "µ".into(),
-        -3 => "m".into(),
-        0 => "".into(),
-        3 => "k".into(),
-        6 => "M".into(),
-        9 => "G".into(),
-        12 => "T".into(),
-        15 => "P".into(),
-        18 => "E".into(),
-        21 => "Z".into(),
-        24 => "Y".into(),
-        27 => "R".into(),
-        30 => "Q".into(),
-        other => format!(".10^{other}").into(),
-    };
-    let base = coord / 10.0f32.powi(si_power);
-    format!("{base:.0}{si_prefix}")
+/// Render a measured value's axis label
+///
+/// Ratios and derived metrics are dimensionless and displayed as is, other
+/// measurements are displayed with SI prefixes.
+fn value_label(args: &Args, traces: &Traces, value: &f32) -> String {
+    if let Some(Comparison::Ratio(_)) = traces.comparison {
+        format!("{}x", format::number(*value, args.precision, 2))
+    } else if traces.metric == Metric::MeanMedianRatio {
+        format::number(*value, args.precision, 3)
+    } else if traces.metric != Metric::Measurement {
+        format!("{}%", format::number(*value, args.precision, 2))
+    } else {
+        // Logarithmic axes have one tick per power of ten, so only linear axes
+        // need decimals to tell their ticks apart
+        let max_decimals = if is_linear(traces) { 2 } else { 0 };
+        format::si(*value, args.precision, max_decimals)
+    }
 }
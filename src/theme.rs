@@ -0,0 +1,141 @@
+//! Colors used when drawing plots
+
+use crate::Result;
+use anyhow::{ensure, Context};
+use plotters::style::{RGBAColor, RGBColor};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Colors used when drawing plots
+///
+/// Themes are loaded from JSON files where each color is a `#rrggbb` or
+/// `#rrggbbaa` string. Colors which are not specified keep their default
+/// value, so a theme file only needs to list the colors that it changes.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Background of the whole image
+    pub background: Color,
+
+    /// Titles, axis labels and legend labels
+    pub text: Color,
+
+    /// Chart axes
+    pub axis: Color,
+
+    /// Major mesh lines
+    pub bold_mesh: Color,
+
+    /// Minor mesh lines
+    pub light_mesh: Color,
+
+    /// Background of the legend
+    pub legend_background: Color,
+
+    /// Border of the legend
+    pub legend_border: Color,
+
+    /// Reference lines, like the zero line of difference plots
+    pub reference_line: Color,
+
+    /// Shading of the regions of difference plots where traces are slower
+    pub slower_region: Color,
+
+    /// Shading of the target performance window of `--target-band`
+    pub target_region: Color,
+
+    /// Speedup matrix cells where the row trace is faster than the column one
+    pub faster_cell: Color,
+
+    /// Speedup matrix cells where the row trace is slower than the column one
+    pub slower_cell: Color,
+
+    /// Colors of the traces, which are reused cyclically if there are more
+    /// traces than colors (alpha is ignored). If empty, trace colors are picked
+    /// along a rainbow gradient.
+    pub palette: Vec<Color>,
+}
+//
+impl Theme {
+    /// Load a theme from a JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read(path).context("reading the theme file")?;
+        serde_json::from_slice(&json).context("decoding the theme file")
+    }
+
+    /// Load a theme from a JSON file, in a form suitable for clap
+    pub(crate) fn parse_arg(path: &str) -> std::result::Result<Self, String> {
+        Self::from_file(Path::new(path)).map_err(|e| format!("{e:#}"))
+    }
+}
+//
+impl Default for Theme {
+    fn default() -> Self {
+        let gray = |alpha| Color(RGBAColor(0, 0, 0, alpha));
+        Self {
+            background: Color(RGBAColor(255, 255, 255, 1.0)),
+            text: gray(1.0),
+            axis: gray(1.0),
+            bold_mesh: gray(0.2),
+            light_mesh: gray(0.1),
+            legend_background: Color(RGBAColor(255, 255, 255, 1.0)),
+            legend_border: gray(1.0),
+            reference_line: gray(1.0),
+            slower_region: Color(RGBAColor(255, 0, 0, 0.08)),
+            target_region: Color(RGBAColor(0, 160, 0, 0.12)),
+            faster_cell: Color(RGBAColor(0, 170, 0, 1.0)),
+            slower_cell: Color(RGBAColor(220, 0, 0, 1.0)),
+            palette: Vec::new(),
+        }
+    }
+}
+
+/// Color from a theme file
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub RGBAColor);
+//
+impl Color {
+    /// Opaque version of this color
+    pub fn opaque(self) -> RGBColor {
+        let RGBAColor(r, g, b, _) = self.0;
+        RGBColor(r, g, b)
+    }
+}
+//
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        let RGBAColor(r, g, b, alpha) = color.0;
+        let alpha = (alpha * 255.0).round() as u8;
+        format!("#{r:02x}{g:02x}{b:02x}{alpha:02x}")
+    }
+}
+//
+impl TryFrom<String> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let digits = value
+            .strip_prefix('#')
+            .with_context(|| format!("color {value:?} should start with #"))?;
+        ensure!(
+            (digits.len() == 6 || digits.len() == 8) && digits.is_ascii(),
+            "color {value:?} should have the #rrggbb or #rrggbbaa format"
+        );
+        let component = |idx: usize| {
+            u8::from_str_radix(&digits[2 * idx..2 * idx + 2], 16)
+                .with_context(|| format!("color {value:?} should only contain hex digits"))
+        };
+        let alpha = if digits.len() == 8 {
+            f64::from(component(3)?) / 255.0
+        } else {
+            1.0
+        };
+        Ok(Self(RGBAColor(
+            component(0)?,
+            component(1)?,
+            component(2)?,
+            alpha,
+        )))
+    }
+}
@@ -1,14 +1,25 @@
 //! Benchmark traces suitable for plotting
 
 use crate::{
-    criterion::{self, Benchmark, BenchmarkInfo, Estimate, ThroughputType},
-    Result,
+    criterion::{self, Benchmark, BenchmarkInfo, ConfidenceLevel, Estimate, ThroughputType},
+    format, Args, Result,
+};
+use anyhow::{bail, ensure, Context};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    iter::Peekable,
+    ops::Range,
+    path::Path,
+    str::{CharIndices, FromStr},
+    time::SystemTime,
 };
-use anyhow::ensure;
-use std::{cmp::Ordering, collections::BTreeMap, iter::Peekable, ops::Range, str::CharIndices};
 
 /// Set of traces to be plotted
-#[derive(Clone, Debug, Default, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize)]
 pub struct Traces {
     /// Throughput configuration, if any
     pub throughput: Option<ThroughputType>,
@@ -16,52 +27,194 @@ pub struct Traces {
     /* /// Vertical axis multiple */
     /// Trace data
     pub per_trace_data: Box<[Trace]>,
+
+    /// Number of Criterion benchmarks that the traces were built from
+    pub num_benchmarks: usize,
+
+    /// Oldest and newest benchmark measurement time, if known
+    pub measurement_times: Option<(SystemTime, SystemTime)>,
+
+    /// Comparison with a reference trace that was applied to the data, if any
+    pub comparison: Option<Comparison>,
+
+    /// Criterion's textual description of each problem size
+    ///
+    /// When several benchmarks describe the same problem size differently,
+    /// the first description that was encountered is kept.
+    pub x_labels: BTreeMap<ProblemSize, Box<str>>,
+
+    /// Confidence level of the confidence intervals, if there is any data and
+    /// the intervals come from Criterion
+    pub confidence_level: Option<f32>,
+
+    /// Metric that the trace data was turned into
+    pub metric: Metric,
 }
 //
 impl Traces {
     /// Build traces from criterion benchmark data
-    pub fn new(data: impl IntoIterator<Item = BenchmarkInfo>) -> Result<Self> {
-        let mut name_to_trace = BTreeMap::<TraceName, BTreeMap<usize, MeasurementDisplay>>::new();
-        let mut common_throughput_type = None;
+    pub fn new(args: &Args, data: impl IntoIterator<Item = BenchmarkInfo>) -> Result<Self> {
+        let mut name_to_trace =
+            BTreeMap::<TraceName, BTreeMap<usize, Vec<MeasurementDisplay>>>::new();
+        let mut common_throughput_type = None::<ThroughputType>;
+        let mut num_benchmarks = 0;
+        let mut measurement_times = None::<(SystemTime, SystemTime)>;
+        let mut x_labels = BTreeMap::new();
+        let mut confidence_level = None::<f32>;
         for benchmark_info in data {
             let BenchmarkInfo {
                 benchmark,
                 estimates,
+                measured_at,
+                samples,
             } = benchmark_info;
+            if let Some(measured_at) = measured_at {
+                let (oldest, newest) = measurement_times.get_or_insert((measured_at, measured_at));
+                *oldest = (*oldest).min(measured_at);
+                *newest = (*newest).max(measured_at);
+            }
             let value = benchmark.value_usize()?;
+            let name = benchmark.trace_name();
             let Benchmark {
-                group_id,
-                value_str: _,
+                group_id: _,
+                function_id: _,
+                value_str,
+                directory_name: _,
                 throughput,
             } = benchmark;
             let (throughput_type, untyped_throughput) = criterion::split_throughput(throughput);
+            if untyped_throughput == 0 {
+                args.warn(format_args!(
+                    "data point of {name} at {value} has zero throughput and will not be plotted"
+                ))?;
+                continue;
+            }
+            num_benchmarks += 1;
             if let Some(common_type) = &mut common_throughput_type {
-                ensure!(
-                throughput_type == *common_type,
-                "expected all traces to use throughput type {common_type:?}, but found {throughput_type:?}",
-            );
+                *common_type = common_type.common_type(throughput_type).with_context(|| {
+                    format!(
+                        "expected all traces to use throughput type {common_type:?}, but found {throughput_type:?}"
+                    )
+                })?;
             } else {
                 common_throughput_type = Some(throughput_type);
             }
-            let measurement = MeasurementDisplay::try_from(estimates.median)?
-                .time_to_throughput(untyped_throughput);
+            let mean_median_ratio = estimates.mean_median_ratio();
+            let center = estimates
+                .statistic(args.statistic)
+                .with_context(|| format!("reading estimate of {name} at {value}"))?;
+            let band = estimates
+                .statistic(args.band.unwrap_or(args.statistic))
+                .with_context(|| {
+                    format!("reading the estimate of {name} at {value} that --band selects")
+                })?;
+            // Criterion's confidence interval is not displayed with
+            // --percentile-interval, so its level does not matter
+            let expected_level = if args.percentile_interval.is_some() {
+                ConfidenceLevel::Auto
+            } else {
+                args.confidence_level
+            };
+            let mut measurement = MeasurementDisplay {
+                mean_median_ratio,
+                ..MeasurementDisplay::from_estimates(center, band, expected_level)?
+            };
+            if let Some(interval) = args.percentile_interval {
+                let samples = samples.with_context(|| {
+                    format!(
+                        "no samples found for {name} at {value}, but --percentile-interval \
+                        needs them"
+                    )
+                })?;
+                (measurement.lower_bound, measurement.upper_bound) = samples
+                    .interval(interval)
+                    .with_context(|| format!("reading the samples of {name} at {value}"))?;
+            } else {
+                let level = band.confidence_interval.confidence_level;
+                ensure!(
+                    *confidence_level.get_or_insert(level) == level,
+                    "expected all data points to use the same confidence level, but found \
+                    {}% and {}%",
+                    confidence_level.unwrap_or_default() * 100.0,
+                    level * 100.0
+                );
+            }
+            if args.band.is_some()
+                && !(measurement.lower_bound..=measurement.upper_bound)
+                    .contains(&measurement.point_estimate)
+            {
+                args.warn(format_args!(
+                    "the confidence interval of {name} at {value} that --band selects does \
+                    not contain its point estimate"
+                ))?;
+            }
+            if measurement.lower_bound == measurement.upper_bound {
+                args.warn(format_args!(
+                    "data point of {name} at {value} has a zero-width confidence interval, \
+                    which suggests that Criterion did not collect enough samples or that the data \
+                    is synthetic"
+                ))?;
+            }
+            let measurement = measurement.time_to_throughput(untyped_throughput);
 
-            let trace = name_to_trace.entry(TraceName(group_id)).or_default();
-            ensure!(
-                trace.insert(value, measurement).is_none(),
-                "there should be only one data point associated with value {value}"
-            );
+            x_labels.entry(value).or_insert(value_str);
+            name_to_trace
+                .entry(TraceName(name))
+                .or_default()
+                .entry(value)
+                .or_default()
+                .push(measurement);
         }
         let per_trace_data = name_to_trace
             .into_iter()
-            .map(|(name, data)| Trace {
-                name: name.0,
-                data: data.into_iter().collect(),
+            .map(|(name, data)| {
+                let data = data
+                    .into_iter()
+                    .map(|(x, measurements)| {
+                        let measurement = match (&measurements[..], args.dedup_x) {
+                            ([measurement], _) => *measurement,
+                            (_, None) => bail!(
+                                "there should be only one data point of {} associated with value \
+                                {x}, but there are {} (see --dedup-x)",
+                                name.0,
+                                measurements.len()
+                            ),
+                            (_, Some(dedup)) => {
+                                let combined = dedup.combine(&measurements);
+                                if args.verbose {
+                                    let (min, max) = point_range(&measurements);
+                                    eprintln!(
+                                        "Combined {} measurements of {} at {x} ranging from {} \
+                                        to {} (max/min ratio {:.3})",
+                                        measurements.len(),
+                                        name.0,
+                                        format::si(min, args.precision, 2),
+                                        format::si(max, args.precision, 2),
+                                        max / min
+                                    );
+                                }
+                                combined
+                            }
+                        };
+                        Ok((x, measurement))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Trace {
+                    name: name.0,
+                    data,
+                    baseline: None,
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
         Ok(Self {
             throughput: common_throughput_type,
             per_trace_data,
+            num_benchmarks,
+            measurement_times,
+            comparison: None,
+            x_labels,
+            confidence_level,
+            metric: Metric::default(),
         })
     }
 
@@ -70,41 +223,592 @@ impl Traces {
         self.per_trace_data.len()
     }
 
+    /// Total number of data points across all traces
+    pub fn num_points(&self) -> usize {
+        self.per_trace_data
+            .iter()
+            .map(|trace| trace.data.len())
+            .sum()
+    }
+
     /// Absence of traces
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
-    /// Horizontal and vertical range covered by traces
+    /// Horizontal and vertical range covered by traces and their baselines
     pub fn xy_range(&self) -> (Range<f64>, Range<f32>) {
-        let min_x = self
-            .per_trace_data
-            .iter()
-            .map(|trace| trace.data.first().expect("traces can't be empty").0)
+        let all_points = || {
+            self.per_trace_data
+                .iter()
+                .flat_map(|trace| trace.all_points())
+        };
+        let min_x = all_points()
+            .map(|(x, _)| *x)
             .min()
             .expect("there should be >= 1 trace") as f64;
-        let max_x = self
-            .per_trace_data
-            .iter()
-            .map(|trace| trace.data.last().expect("traces can't be empty").0)
+        let max_x = all_points()
+            .map(|(x, _)| *x)
             .max()
             .expect("there should be >= 1 trace") as f64;
-        let min_y = self
-            .per_trace_data
-            .iter()
-            .flat_map(|trace| trace.data.iter())
+        let min_y = all_points()
             .map(|(_, meas)| meas.lower_bound)
             .min_by(f32::total_cmp)
             .expect("there should be >= 1 trace");
-        let max_y = self
-            .per_trace_data
-            .iter()
-            .flat_map(|trace| trace.data.iter())
+        let max_y = all_points()
             .map(|(_, meas)| meas.upper_bound)
             .max_by(f32::total_cmp)
             .expect("there should be >= 1 trace");
         (min_x..max_x, min_y..max_y)
     }
+
+    /// Attach baseline data to the matching traces
+    ///
+    /// Baseline traces which do not match any current trace are ignored.
+    pub fn attach_baseline(&mut self, baseline: Traces) -> Result<()> {
+        if let (Some(current), Some(baseline)) = (self.throughput, baseline.throughput) {
+            self.throughput = Some(current.common_type(baseline).with_context(|| {
+                format!(
+                    "baseline uses throughput type {baseline:?}, but current data uses {current:?}"
+                )
+            })?);
+        }
+        let mut name_to_baseline = baseline
+            .per_trace_data
+            .into_vec()
+            .into_iter()
+            .map(|trace| (trace.name, trace.data))
+            .collect::<BTreeMap<_, _>>();
+        for trace in self.per_trace_data.iter_mut() {
+            trace.baseline = name_to_baseline.remove(&trace.name);
+        }
+        if let (Some(current), Some(baseline)) = (self.confidence_level, baseline.confidence_level)
+        {
+            ensure!(
+                current == baseline,
+                "baseline uses {}% confidence intervals, but current data uses {}%",
+                baseline * 100.0,
+                current * 100.0
+            );
+        }
+        for (x, label) in baseline.x_labels {
+            self.x_labels.entry(x).or_insert(label);
+        }
+        Ok(())
+    }
+
+    /// Merge traces whose names have the same regex capture
+    ///
+    /// Traces whose name matches `pattern` are renamed after the text captured
+    /// by its first capture group, then traces with identical names are
+    /// merged as described in [`Trace::merge()`].
+    pub fn merge(&mut self, pattern: &Regex) -> Result<()> {
+        ensure!(
+            pattern.captures_len() > 1,
+            "merge regex {pattern} should have a capture group"
+        );
+        let mut name_to_traces = BTreeMap::<TraceName, Vec<Trace>>::new();
+        for trace in std::mem::take(&mut self.per_trace_data).into_vec() {
+            let name = pattern
+                .captures(&trace.name)
+                .and_then(|captures| captures.get(1))
+                .map_or_else(|| trace.name.clone(), |key| key.as_str().into());
+            name_to_traces
+                .entry(TraceName(name))
+                .or_default()
+                .push(trace);
+        }
+        self.per_trace_data = name_to_traces
+            .into_iter()
+            .map(|(name, traces)| Trace::merge(name.0, traces))
+            .collect();
+        Ok(())
+    }
+
+    /// Apply a transform to the data of every trace
+    pub fn transform(&mut self, transform: Transform) {
+        for trace in self.per_trace_data.iter_mut() {
+            trace.transform(transform);
+        }
+    }
+
+    /// Sort the data points of every trace by problem size
+    ///
+    /// Plotting assumes sorted data points, which may not be the case for
+    /// traces that were not built by [`Traces::new()`]. Returns the names of
+    /// the traces whose data points were out of order.
+    pub fn sort_points(&mut self) -> Result<Vec<&str>> {
+        let mut reordered = Vec::new();
+        for trace in self.per_trace_data.iter_mut() {
+            if trace.sort_points()? {
+                reordered.push(&*trace.name);
+            }
+        }
+        Ok(reordered)
+    }
+
+    /// Turn the data of every trace into a derived metric
+    ///
+    /// Derived metrics replace each measurement with a percentage, which has
+    /// no confidence interval. This should only be done once, on traces of
+    /// [`Metric::Measurement`].
+    pub fn to_metric(&mut self, metric: Metric) {
+        let derive: fn(&MeasurementDisplay) -> f32 = match metric {
+            Metric::Measurement => return,
+            Metric::Cv => MeasurementDisplay::coefficient_of_variation,
+            Metric::CiWidth => |meas| 100.0 * meas.relative_ci_width(),
+            Metric::MeanMedianRatio => |meas| meas.mean_median_ratio,
+        };
+        for trace in self.per_trace_data.iter_mut() {
+            for dataset in trace.datasets_mut() {
+                for (_, meas) in dataset.iter_mut() {
+                    *meas = meas.derived(derive(meas));
+                }
+            }
+        }
+        self.metric = metric;
+    }
+
+    /// Smooth every trace with a rolling geometric mean
+    ///
+    /// See [`Trace::smooth()`] for details.
+    pub fn smooth(&mut self, window: usize) {
+        for trace in self.per_trace_data.iter_mut() {
+            trace.smooth(window);
+        }
+    }
+
+    /// Constrain error bars to within a factor of the point estimate
+    ///
+    /// This keeps a few wild confidence intervals on noisy benchmarks from
+    /// dominating the automatic vertical axis range.
+    pub fn clamp_errors(&mut self, max_factor: f32) -> Result<()> {
+        ensure!(
+            max_factor >= 1.0,
+            "the maximal error factor must be at least 1, got {max_factor}"
+        );
+        for trace in self.per_trace_data.iter_mut() {
+            for (_, meas) in trace.datasets_mut().flatten() {
+                meas.lower_bound = meas.lower_bound.max(meas.point_estimate / max_factor);
+                meas.upper_bound = meas.upper_bound.min(meas.point_estimate * max_factor);
+            }
+        }
+        Ok(())
+    }
+
+    /// Discard traces whose measurements do not vary with problem size
+    ///
+    /// A trace is considered flat if its [`Trace::relative_spread()`] is below
+    /// `threshold`. Traces with a single data point are always kept. The names
+    /// of the discarded traces are returned along with their relative spread.
+    pub fn drop_flat(&mut self, threshold: f32) -> Vec<(Box<str>, f32)> {
+        let mut dropped = Vec::new();
+        self.per_trace_data = std::mem::take(&mut self.per_trace_data)
+            .into_vec()
+            .into_iter()
+            .filter_map(|trace| {
+                let spread = trace.relative_spread();
+                if trace.data.len() > 1 && spread < threshold {
+                    dropped.push((trace.name, spread));
+                    None
+                } else {
+                    Some(trace)
+                }
+            })
+            .collect();
+        dropped
+    }
+
+    /// Split the traces into groups, according to the part of their name
+    /// before the first `/`
+    ///
+    /// Within each group, traces are renamed to the rest of their name, or
+    /// keep their name if it has no `/`. Groups are sorted by name, and their
+    /// baselines and other properties are kept.
+    pub fn split(mut self) -> Vec<(Box<str>, Self)> {
+        let mut groups = BTreeMap::<Box<str>, Vec<Trace>>::new();
+        for mut trace in std::mem::take(&mut self.per_trace_data).into_vec() {
+            let (group, sub_name) = match trace.name.split_once('/') {
+                Some((group, sub_name)) => (group.into(), sub_name.into()),
+                None => (trace.name.clone(), trace.name.clone()),
+            };
+            trace.name = sub_name;
+            groups.entry(group).or_default().push(trace);
+        }
+        groups
+            .into_iter()
+            .map(|(group, traces)| {
+                let traces = Self {
+                    per_trace_data: traces.into(),
+                    ..self.clone()
+                };
+                (group, traces)
+            })
+            .collect()
+    }
+
+    /// Discard the traces with the specified names
+    ///
+    /// Returns the names which did not match any trace.
+    pub fn hide<'names>(&mut self, names: &'names [Box<str>]) -> Vec<&'names str> {
+        let unmatched = names
+            .iter()
+            .filter(|name| !self.per_trace_data.iter().any(|trace| trace.name == **name))
+            .map(|name| &**name)
+            .collect();
+        self.per_trace_data = std::mem::take(&mut self.per_trace_data)
+            .into_vec()
+            .into_iter()
+            .filter(|trace| !names.contains(&trace.name))
+            .collect();
+        unmatched
+    }
+
+    /// Only keep a limited number of traces, picked according to some strategy
+    ///
+    /// Traces are ranked by their typical performance, as measured by
+    /// [`Trace::geomean_point_estimate()`]. The retained traces keep their
+    /// original ordering.
+    pub fn limit(&mut self, max_traces: usize, strategy: TraceLimitStrategy) {
+        // Check if there is anything to do
+        let num_traces = self.len();
+        if num_traces <= max_traces {
+            return;
+        }
+
+        // Rank traces from best to worst
+        let ranking = self
+            .ranking()
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        // Pick the traces that we are going to keep
+        let mut keep = vec![false; num_traces];
+        let kept_ranks = (0..max_traces).map(|idx| match strategy {
+            TraceLimitStrategy::Top => idx,
+            TraceLimitStrategy::Bottom => num_traces - max_traces + idx,
+            TraceLimitStrategy::Spread if max_traces == 1 => (num_traces - 1) / 2,
+            TraceLimitStrategy::Spread => idx * (num_traces - 1) / (max_traces - 1),
+        });
+        for rank in kept_ranks {
+            keep[ranking[rank]] = true;
+        }
+        let mut keep = keep.into_iter();
+        self.per_trace_data = std::mem::take(&mut self.per_trace_data)
+            .into_vec()
+            .into_iter()
+            .filter(|_| keep.next().expect("one flag per trace"))
+            .collect();
+    }
+
+    /// Score of each trace, from best to worst
+    ///
+    /// The score of a trace is the geometric mean of its point estimates
+    /// across all problem sizes, see [`Trace::geomean_point_estimate()`].
+    pub fn scores(&self) -> Vec<(&str, f32)> {
+        self.ranking()
+            .into_iter()
+            .map(|(idx, score)| (&*self.per_trace_data[idx].name, score))
+            .collect()
+    }
+
+    /// Indices of the traces from best to worst, along with their score
+    fn ranking(&self) -> Vec<(usize, f32)> {
+        let mut ranking = self
+            .per_trace_data
+            .iter()
+            .map(Trace::geomean_point_estimate)
+            .enumerate()
+            .collect::<Vec<_>>();
+        ranking.sort_by(|(_, score1), (_, score2)| {
+            let order = score1.total_cmp(score2);
+            if self.higher_is_better() {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        ranking
+    }
+
+    /// Fill in the problem sizes that some traces were measured at, but not
+    /// others, by interpolating between measurements
+    ///
+    /// Each trace gets a data point at every problem size where another trace
+    /// was measured, as long as it lies between two of its own measurements.
+    /// Baselines are completed in the same way using the problem sizes of
+    /// other baselines. Interpolation is linear in log-log space. Returns the
+    /// number of data points that were added.
+    pub fn interpolate_missing(&mut self) -> usize {
+        let data_sizes = self
+            .per_trace_data
+            .iter()
+            .flat_map(|trace| trace.data.iter().map(|(x, _)| *x))
+            .collect::<BTreeSet<_>>();
+        let baseline_sizes = self
+            .per_trace_data
+            .iter()
+            .filter_map(|trace| trace.baseline.as_deref())
+            .flat_map(|baseline| baseline.iter().map(|(x, _)| *x))
+            .collect::<BTreeSet<_>>();
+        let mut num_added = 0;
+        for trace in self.per_trace_data.iter_mut() {
+            num_added += interpolate_missing(&mut trace.data, &data_sizes);
+            if let Some(baseline) = &mut trace.baseline {
+                num_added += interpolate_missing(baseline, &baseline_sizes);
+            }
+        }
+        num_added
+    }
+
+    /// Compare every trace with a reference trace
+    ///
+    /// Each measurement is replaced with its difference from, or ratio to, the
+    /// measurement of the reference trace at the same problem size. Only
+    /// problem sizes where the reference trace has data are kept, and traces
+    /// which have no such problem size are discarded. Baselines are compared
+    /// with the reference trace's baseline, if any. Error bars are widened to
+    /// account for the uncertainty of the reference measurement.
+    pub fn compare_to(&mut self, comparison: Comparison) -> Result<()> {
+        let reference = self
+            .per_trace_data
+            .iter()
+            .find(|trace| &*trace.name == comparison.reference())
+            .with_context(|| {
+                format!(
+                    "reference trace {} is not part of the plotted traces",
+                    comparison.reference()
+                )
+            })?
+            .clone();
+        let combine = match comparison {
+            Comparison::Difference(_) => MeasurementDisplay::minus,
+            Comparison::Ratio(_) => MeasurementDisplay::divided_by,
+        };
+        for trace in self.per_trace_data.iter_mut() {
+            trace.data = compare_points(&trace.data, &reference.data, combine);
+            trace.baseline = match (trace.baseline.take(), &reference.baseline) {
+                (Some(baseline), Some(reference)) => {
+                    Some(compare_points(&baseline, reference, combine))
+                }
+                _ => None,
+            };
+        }
+        self.per_trace_data = std::mem::take(&mut self.per_trace_data)
+            .into_vec()
+            .into_iter()
+            .filter(|trace| !trace.data.is_empty())
+            .collect();
+        self.comparison = Some(comparison);
+        Ok(())
+    }
+
+    /// Find the data points where traces regressed with respect to their
+    /// baseline by more than `threshold` percent
+    ///
+    /// Point estimates are compared at each problem size where both the trace
+    /// and its baseline were measured. The offending traces are returned along
+    /// with the problem size and the magnitude of the regression in percent.
+    ///
+    /// Traces and problem sizes without baseline data cannot be checked, which
+    /// is reported as a warning, and it is an error if no trace has any.
+    pub fn regressions(
+        &self,
+        args: &Args,
+        threshold: f32,
+    ) -> Result<Vec<(&str, ProblemSize, f32)>> {
+        ensure!(
+            self.per_trace_data
+                .iter()
+                .any(|trace| trace.baseline.as_ref().is_some_and(|b| !b.is_empty())),
+            "cannot check for regressions, as no trace has baseline data"
+        );
+        let mut regressions = Vec::new();
+        for trace in self.per_trace_data.iter() {
+            let Some(baseline) = &trace.baseline else {
+                args.warn(format_args!(
+                    "trace {} has no baseline data and cannot be checked for regressions",
+                    trace.name
+                ))?;
+                continue;
+            };
+            for (x, meas) in trace.data.iter() {
+                let Ok(idx) = baseline.binary_search_by_key(x, |(x, _)| *x) else {
+                    args.warn(format_args!(
+                        "trace {} has no baseline data at problem size {x} and cannot be \
+                        checked for regressions there",
+                        trace.name
+                    ))?;
+                    continue;
+                };
+                let reference = baseline[idx].1.point_estimate;
+                let change = 100.0 * (meas.point_estimate - reference) / reference.abs();
+                let regression = if self.higher_is_better() {
+                    -change
+                } else {
+                    change
+                };
+                if regression > threshold {
+                    regressions.push((&*trace.name, *x, regression));
+                }
+            }
+        }
+        Ok(regressions)
+    }
+
+    /// Truth that higher measurements mean better performance
+    ///
+    /// This is true of throughputs, but not of execution times.
+    pub fn higher_is_better(&self) -> bool {
+        self.throughput.is_some() && self.metric == Metric::Measurement
+    }
+
+    /// Pick a single data point from each trace
+    ///
+    /// If a problem size is specified, the data point at this problem size is
+    /// picked, otherwise each trace must have a single data point.
+    pub fn single_points(
+        &self,
+        problem_size: Option<ProblemSize>,
+    ) -> Result<Vec<(&str, MeasurementDisplay)>> {
+        self.per_trace_data
+            .iter()
+            .map(|trace| {
+                let point = if let Some(problem_size) = problem_size {
+                    trace
+                        .data
+                        .iter()
+                        .find(|(x, _)| *x == problem_size)
+                        .with_context(|| {
+                            format!(
+                                "trace {} has no data at problem size {problem_size}",
+                                trace.name
+                            )
+                        })?
+                } else {
+                    ensure!(
+                        trace.data.len() == 1,
+                        "trace {} has several data points, please specify a problem size",
+                        trace.name
+                    );
+                    &trace.data[0]
+                };
+                Ok((&*trace.name, point.1))
+            })
+            .collect()
+    }
+
+    /// Build traces that track the evolution of performance across snapshots
+    ///
+    /// Each trace of the output gets one data point per snapshot, which is the
+    /// data point of the same-named input trace at the specified problem size.
+    /// Snapshots are laid out as successive powers of two, so that they are
+    /// evenly spaced on the logarithmic horizontal axis, and labeled in
+    /// [`x_labels`](Self::x_labels).
+    pub fn track(snapshots: Vec<(Box<str>, Traces)>, problem_size: ProblemSize) -> Result<Self> {
+        ensure!(
+            snapshots.len() < ProblemSize::BITS as usize,
+            "cannot track more than {} snapshots",
+            ProblemSize::BITS - 1
+        );
+        let mut tracked = Self::default();
+        let mut tracked_traces = Vec::<(Box<str>, Vec<(ProblemSize, MeasurementDisplay)>)>::new();
+        for (idx, (label, traces)) in snapshots.into_iter().enumerate() {
+            // Check that snapshots can share a plot
+            if idx == 0 {
+                tracked.throughput = traces.throughput;
+                tracked.comparison = traces.comparison;
+                tracked.confidence_level = traces.confidence_level;
+                tracked.metric = traces.metric;
+            } else {
+                let (expected, actual) = (tracked.throughput, traces.throughput);
+                if (expected, actual) != (None, None) {
+                    tracked.throughput = Some(
+                        expected
+                            .zip(actual)
+                            .and_then(|(expected, actual)| expected.common_type(actual))
+                            .with_context(|| {
+                                format!(
+                                    "expected all snapshots to use throughput type {expected:?}, \
+                                    but snapshot {label} uses {actual:?}"
+                                )
+                            })?,
+                    );
+                }
+                if let Some(level) = traces.confidence_level {
+                    ensure!(
+                        *tracked.confidence_level.get_or_insert(level) == level,
+                        "expected all snapshots to use the same confidence level, but found \
+                        {}% and {}%",
+                        tracked.confidence_level.unwrap_or_default() * 100.0,
+                        level * 100.0
+                    );
+                }
+            }
+
+            // Merge the snapshot's metadata
+            tracked.num_benchmarks += traces.num_benchmarks;
+            if let Some((oldest, newest)) = traces.measurement_times {
+                let times = tracked.measurement_times.get_or_insert((oldest, newest));
+                times.0 = times.0.min(oldest);
+                times.1 = times.1.max(newest);
+            }
+            let x = 1 << idx;
+            tracked.x_labels.insert(x, label);
+
+            // Record the snapshot's data points at the tracked problem size
+            for trace in traces.per_trace_data.into_vec() {
+                let Some(&(_, meas)) = trace.data.iter().find(|(x, _)| *x == problem_size) else {
+                    continue;
+                };
+                match tracked_traces
+                    .iter_mut()
+                    .find(|(name, _)| *name == trace.name)
+                {
+                    Some((_, data)) => data.push((x, meas)),
+                    None => tracked_traces.push((trace.name, vec![(x, meas)])),
+                }
+            }
+        }
+        tracked.per_trace_data = tracked_traces
+            .into_iter()
+            .map(|(name, data)| Trace {
+                name,
+                data: data.into(),
+                baseline: None,
+            })
+            .collect();
+        Ok(tracked)
+    }
+}
+
+/// Criterion data snapshot whose evolution is tracked by `--track`
+///
+/// On the command line, this is specified as `<label>=<path>`, where the label
+/// identifies the snapshot on the horizontal axis (e.g. a commit hash or a
+/// date) and the path is the root of the Rust project where it was measured.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Label of the snapshot on the horizontal axis
+    pub label: Box<str>,
+
+    /// Path to the root of the Rust project where the snapshot was measured
+    pub input_path: Box<Path>,
+}
+//
+impl FromStr for Snapshot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (label, input_path) = s
+            .split_once('=')
+            .context("tracked snapshots should have the <label>=<path> format")?;
+        Ok(Self {
+            label: label.trim().into(),
+            input_path: Path::new(input_path).into(),
+        })
+    }
 }
 
 /// Trace name newtype with a more sensible ordering
@@ -203,46 +907,546 @@ enum TextOrNumber<'source> {
 }
 
 /// Trace to be plotted
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize)]
 pub struct Trace {
     /// Name of the trace
     pub name: Box<str>,
 
     /// Data to be plotted
     pub data: Box<[(ProblemSize, MeasurementDisplay)]>,
+
+    /// Data from a baseline that this trace is compared with, if any
+    pub baseline: Option<Box<[(ProblemSize, MeasurementDisplay)]>>,
+}
+//
+impl Trace {
+    /// Data points of this trace and its baseline, if any
+    pub fn all_points(&self) -> impl Iterator<Item = &(ProblemSize, MeasurementDisplay)> {
+        self.data
+            .iter()
+            .chain(self.baseline.iter().flat_map(|b| b.iter()))
+    }
+
+    /// Sort the data points of this trace and its baseline by problem size
+    ///
+    /// Returns `true` if some data points were out of order. It is an error
+    /// for a dataset to have several data points at the same problem size.
+    pub fn sort_points(&mut self) -> Result<bool> {
+        let mut reordered = false;
+        let Self {
+            name,
+            data,
+            baseline,
+        } = self;
+        for dataset in std::iter::once(&mut data[..]).chain(baseline.as_deref_mut()) {
+            if !dataset.is_sorted_by_key(|(x, _)| *x) {
+                dataset.sort_by_key(|(x, _)| *x);
+                reordered = true;
+            }
+            if let Some(window) = dataset.windows(2).find(|window| window[0].0 == window[1].0) {
+                bail!(
+                    "trace {name} has several data points at problem size {}",
+                    window[0].0
+                );
+            }
+        }
+        Ok(reordered)
+    }
+
+    /// Mutable access to the dataset of this trace and its baseline, if any
+    fn datasets_mut(&mut self) -> impl Iterator<Item = &mut [(ProblemSize, MeasurementDisplay)]> {
+        std::iter::once(&mut self.data[..]).chain(self.baseline.as_deref_mut())
+    }
+
+    /// Merge several traces into a single one with the specified name
+    ///
+    /// At each problem size, the point estimate of the merged trace is the
+    /// geometric mean of the point estimates of the source traces, and its
+    /// confidence interval is the envelope of their confidence intervals.
+    /// Baselines are merged in the same way.
+    pub fn merge(name: Box<str>, traces: Vec<Trace>) -> Self {
+        let mut data = BTreeMap::<ProblemSize, Vec<MeasurementDisplay>>::new();
+        let mut baseline = None::<BTreeMap<ProblemSize, Vec<MeasurementDisplay>>>;
+        for trace in traces {
+            for (x, meas) in trace.data.iter() {
+                data.entry(*x).or_default().push(*meas);
+            }
+            for (x, meas) in trace.baseline.iter().flat_map(|b| b.iter()) {
+                let baseline = baseline.get_or_insert_with(BTreeMap::new);
+                baseline.entry(*x).or_default().push(*meas);
+            }
+        }
+        let merge_dataset = |dataset: BTreeMap<ProblemSize, Vec<MeasurementDisplay>>| {
+            dataset
+                .into_iter()
+                .map(|(x, measurements)| (x, MeasurementDisplay::merge(&measurements)))
+                .collect()
+        };
+        Self {
+            name,
+            data: merge_dataset(data),
+            baseline: baseline.map(merge_dataset),
+        }
+    }
+
+    /// Geometric mean of the point estimates of this trace
+    pub fn geomean_point_estimate(&self) -> f32 {
+        geomean(self.data.iter().map(|(_, meas)| meas.point_estimate))
+    }
+
+    /// Relative standard deviation of the point estimates of this trace
+    pub fn relative_spread(&self) -> f32 {
+        let num_points = self.data.len() as f64;
+        let values = || {
+            self.data
+                .iter()
+                .map(|(_, meas)| f64::from(meas.point_estimate))
+        };
+        let mean = values().sum::<f64>() / num_points;
+        let variance = values().map(|value| (value - mean).powi(2)).sum::<f64>() / num_points;
+        (variance.sqrt() / mean.abs()) as f32
+    }
+
+    /// Point estimate of this trace at an arbitrary problem size
+    ///
+    /// Between data points, the point estimate is linearly interpolated with
+    /// respect to the logarithm of the problem size. Outside of the measured
+    /// range, there is no estimate.
+    pub fn interpolate(&self, x: f64) -> Option<f32> {
+        let idx = self.data.partition_point(|(x2, _)| (*x2 as f64) < x);
+        let (x1, meas1) = *self.data.get(idx)?;
+        if x1 as f64 == x {
+            return Some(meas1.point_estimate);
+        }
+        let (x0, meas0) = *self.data.get(idx.checked_sub(1)?)?;
+        let (x0, x1) = ((x0 as f64).ln(), (x1 as f64).ln());
+        let t = ((x.ln() - x0) / (x1 - x0)) as f32;
+        Some(meas0.point_estimate + t * (meas1.point_estimate - meas0.point_estimate))
+    }
+
+    /// Problem sizes where the point estimates of this trace and another
+    /// trace cross, interpolating between data points
+    pub fn crossovers(&self, other: &Trace) -> Vec<f64> {
+        // Evaluate both traces at all problem sizes where either was measured
+        let mut sizes = self
+            .data
+            .iter()
+            .chain(other.data.iter())
+            .map(|(x, _)| *x as f64)
+            .collect::<Vec<_>>();
+        sizes.sort_by(f64::total_cmp);
+        sizes.dedup();
+        let differences = sizes
+            .into_iter()
+            .filter_map(|x| Some((x, self.interpolate(x)? - other.interpolate(x)?)))
+            .collect::<Vec<_>>();
+
+        // Look for sign changes of the difference between the traces
+        let mut crossovers = Vec::new();
+        for (idx, &(x1, diff1)) in differences.iter().enumerate() {
+            if diff1 == 0.0 {
+                crossovers.push(x1);
+                continue;
+            }
+            let Some(&(x0, diff0)) = idx.checked_sub(1).map(|idx| &differences[idx]) else {
+                continue;
+            };
+            if diff0 != 0.0 && (diff0 < 0.0) != (diff1 < 0.0) {
+                let t = f64::from(diff0 / (diff0 - diff1));
+                crossovers.push((x0.ln() + t * (x1.ln() - x0.ln())).exp());
+            }
+        }
+        crossovers
+    }
+
+    /// Smooth this trace's data with a rolling geometric mean
+    ///
+    /// Each measurement is replaced with the geometric mean of the `window`
+    /// measurements centered on it. Near the ends of the trace, the window
+    /// shrinks to the measurements that are available.
+    pub fn smooth(&mut self, window: usize) {
+        let (before, after) = (window.saturating_sub(1) / 2, window / 2);
+        for dataset in self.datasets_mut() {
+            let original = dataset.iter().map(|(_, meas)| *meas).collect::<Vec<_>>();
+            for (idx, (_, meas)) in dataset.iter_mut().enumerate() {
+                let start = idx.saturating_sub(before);
+                let end = (idx + after + 1).min(original.len());
+                *meas = MeasurementDisplay::geomean(&original[start..end]);
+            }
+        }
+    }
+
+    /// Apply a transform to this trace's data
+    pub fn transform(&mut self, transform: Transform) {
+        let is_better: fn(f32, f32) -> bool = match transform {
+            Transform::None => return,
+            Transform::RunningMin => |new, best| new < best,
+            Transform::RunningMax => |new, best| new > best,
+        };
+        for dataset in self.datasets_mut() {
+            let mut best = None::<MeasurementDisplay>;
+            for (_, meas) in dataset.iter_mut() {
+                match best {
+                    Some(best) if !is_better(meas.point_estimate, best.point_estimate) => {
+                        *meas = best
+                    }
+                    _ => best = Some(*meas),
+                }
+            }
+        }
+    }
+}
+
+/// Geometric mean of some positive values
+fn geomean(values: impl ExactSizeIterator<Item = f32>) -> f32 {
+    let num_values = values.len();
+    let log_sum = values.map(|value| f64::from(value).ln()).sum::<f64>();
+    (log_sum / num_values as f64).exp() as f32
+}
+
+/// Add interpolated data points to a dataset at the specified problem sizes,
+/// if they lie between two of its measurements
+///
+/// Returns the number of data points that were added.
+fn interpolate_missing(
+    dataset: &mut Box<[(ProblemSize, MeasurementDisplay)]>,
+    sizes: &BTreeSet<ProblemSize>,
+) -> usize {
+    let mut added = Vec::new();
+    for &x in sizes {
+        let idx = dataset.partition_point(|(x2, _)| *x2 < x);
+        if idx == 0 || idx == dataset.len() || dataset[idx].0 == x {
+            continue;
+        }
+        let (x0, meas0) = dataset[idx - 1];
+        let (x1, meas1) = dataset[idx];
+        let t = ((x as f64).ln() - (x0 as f64).ln()) / ((x1 as f64).ln() - (x0 as f64).ln());
+        added.push((x, meas0.log_interpolate(meas1, t as f32)));
+    }
+    let num_added = added.len();
+    if num_added > 0 {
+        let mut data = std::mem::take(dataset).into_vec();
+        data.extend(added);
+        data.sort_by_key(|(x, _)| *x);
+        *dataset = data.into();
+    }
+    num_added
+}
+
+/// Smallest and largest point estimate of some measurements
+fn point_range(measurements: &[MeasurementDisplay]) -> (f32, f32) {
+    measurements
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), meas| {
+            (min.min(meas.point_estimate), max.max(meas.point_estimate))
+        })
+}
+
+/// Combine the measurements of a dataset with those of a reference dataset
+///
+/// Both datasets must be sorted by problem size, and only problem sizes where
+/// the reference dataset has a measurement are kept.
+fn compare_points(
+    data: &[(ProblemSize, MeasurementDisplay)],
+    reference: &[(ProblemSize, MeasurementDisplay)],
+    combine: fn(MeasurementDisplay, MeasurementDisplay) -> MeasurementDisplay,
+) -> Box<[(ProblemSize, MeasurementDisplay)]> {
+    data.iter()
+        .filter_map(|&(x, meas)| {
+            let idx = reference.binary_search_by_key(&x, |&(x, _)| x).ok()?;
+            Some((x, combine(meas, reference[idx].1)))
+        })
+        .collect()
+}
+
+/// Comparison of every trace with a reference trace
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize)]
+pub enum Comparison {
+    /// Signed difference with the named reference trace
+    Difference(Box<str>),
+
+    /// Ratio to the named reference trace
+    Ratio(Box<str>),
+}
+//
+impl Comparison {
+    /// Name of the reference trace
+    pub fn reference(&self) -> &str {
+        match self {
+            Self::Difference(reference) | Self::Ratio(reference) => reference,
+        }
+    }
+}
+
+/// Way to combine the measurements of a trace that share a problem size
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DedupX {
+    /// Keep the measurement with the median point estimate
+    Median,
+
+    /// Keep the measurement with the highest point estimate
+    Max,
+}
+//
+impl DedupX {
+    /// Pick one of several measurements
+    ///
+    /// For an even number of measurements, the lower median is kept.
+    fn combine(self, measurements: &[MeasurementDisplay]) -> MeasurementDisplay {
+        let mut sorted = measurements.to_vec();
+        sorted.sort_by(|meas1, meas2| meas1.point_estimate.total_cmp(&meas2.point_estimate));
+        match self {
+            Self::Median => sorted[(sorted.len() - 1) / 2],
+            Self::Max => *sorted
+                .last()
+                .expect("there should be measurements to combine"),
+        }
+    }
+}
+
+/// Metric that is plotted for each measurement
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// Measured execution time or throughput
+    #[default]
+    #[value(aliases = ["throughput", "time"])]
+    Measurement,
+
+    /// Coefficient of variation, i.e. standard error divided by point
+    /// estimate, in percent
+    Cv,
+
+    /// Width of the confidence interval, relative to the point estimate, in
+    /// percent
+    CiWidth,
+
+    /// Ratio of the mean execution time to the median execution time, which
+    /// is above 1 when slow outliers skew the distribution of timings
+    MeanMedianRatio,
+}
+
+/// Strategy used to pick which traces are kept when there are too many
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraceLimitStrategy {
+    /// Keep the best-performing traces
+    #[default]
+    Top,
+
+    /// Keep the worst-performing traces
+    Bottom,
+
+    /// Keep traces spread evenly from best to worst performance
+    Spread,
+}
+
+/// Transform applied to the data of each trace before plotting
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    /// Plot measurements as they are
+    #[default]
+    None,
+
+    /// Replace each measurement with the one that has the lowest point
+    /// estimate at this problem size or below
+    RunningMin,
+
+    /// Replace each measurement with the one that has the highest point
+    /// estimate at this problem size or below
+    RunningMax,
 }
 
 /// Horizontal coordinate of a criterion benchmark
 pub type ProblemSize = usize;
 
 /// Summary of a criterion benchmark measurement for display
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Serialize)]
 pub struct MeasurementDisplay {
-    /// 95% lower bound
+    /// Lower bound of the confidence interval (usually 95%)
     pub lower_bound: f32,
 
     /// Central value
     pub point_estimate: f32,
 
-    /// 95% upper bound
+    /// Upper bound of the confidence interval (usually 95%)
     pub upper_bound: f32,
+
+    /// Standard error of the point estimate
+    pub standard_error: f32,
+
+    /// Ratio of the mean execution time to the median execution time
+    pub mean_median_ratio: f32,
+
+    /// Point estimate of the original timing measurement (ns)
+    ///
+    /// This is kept around after conversion to throughput, so that both the
+    /// timing and the throughput can be reported.
+    pub time_ns: f32,
 }
 //
 impl MeasurementDisplay {
+    /// Width of the confidence interval, relative to the point estimate
+    pub fn relative_ci_width(&self) -> f32 {
+        (self.upper_bound - self.lower_bound) / self.point_estimate
+    }
+
+    /// Coefficient of variation of this measurement, in percent
+    pub fn coefficient_of_variation(&self) -> f32 {
+        100.0 * self.standard_error / self.point_estimate.abs()
+    }
+
+    /// Measurement of a metric derived from this measurement, whose
+    /// confidence interval is reduced to its value
+    fn derived(&self, value: f32) -> Self {
+        Self {
+            lower_bound: value,
+            point_estimate: value,
+            upper_bound: value,
+            standard_error: 0.0,
+            mean_median_ratio: self.mean_median_ratio,
+            time_ns: self.time_ns,
+        }
+    }
+
+    /// Turn criterion Estimates into a timing measurement, checking that they
+    /// have the expected confidence level
+    ///
+    /// The point estimate is taken from `center`, while the confidence interval
+    /// and standard error are taken from `band`, which is usually the same
+    /// estimate. The mean-median ratio is unknown at this point and set to 1.
+    fn from_estimates(
+        center: &Estimate,
+        band: &Estimate,
+        expected_level: ConfidenceLevel,
+    ) -> Result<Self> {
+        if let ConfidenceLevel::Fixed(expected) = expected_level {
+            let actual = band.confidence_interval.confidence_level;
+            ensure!(
+                actual == expected,
+                "Expecting {}% confidence intervals from Criterion, but got {}% \
+                (see --confidence-level)",
+                expected * 100.0,
+                actual * 100.0
+            );
+        }
+        Ok(Self {
+            point_estimate: center.point_estimate,
+            lower_bound: band.confidence_interval.lower_bound,
+            upper_bound: band.confidence_interval.upper_bound,
+            standard_error: band.standard_error,
+            mean_median_ratio: 1.0,
+            time_ns: center.point_estimate,
+        })
+    }
+
     /// Turn a timing measurement into a throughput measurement
     ///
     /// This function has two correctness preconditions:
     ///
     /// - The source measurement must be a timing measurement in nanoseconds
-    ///   (e.g. the direct result of converting a criterion median Estimate)
+    ///   (e.g. the direct result of converting a criterion Estimate)
     /// - For the final plot to make sense, all measurements must have the same
     ///   [`ThroughputType`].
+    ///
+    /// The standard error is propagated to first order, which preserves the
+    /// coefficient of variation.
     fn time_to_throughput(self, untyped_throughput: u64) -> Self {
         let untyped_throughput = untyped_throughput as f32;
+        let point_estimate = untyped_throughput / (self.point_estimate * 1e-9);
         Self {
-            point_estimate: untyped_throughput / (self.point_estimate * 1e-9),
+            point_estimate,
             lower_bound: untyped_throughput / (self.upper_bound * 1e-9),
             upper_bound: untyped_throughput / (self.lower_bound * 1e-9),
+            standard_error: point_estimate * self.standard_error / self.point_estimate,
+            mean_median_ratio: self.mean_median_ratio,
+            time_ns: self.time_ns,
+        }
+    }
+
+    /// Merge several measurements of the same quantity
+    ///
+    /// The point estimates and standard errors are combined with a geometric
+    /// mean, and the resulting confidence interval is the envelope of the
+    /// input ones.
+    fn merge(measurements: &[Self]) -> Self {
+        assert!(!measurements.is_empty(), "cannot merge zero measurements");
+        Self {
+            lower_bound: measurements
+                .iter()
+                .map(|meas| meas.lower_bound)
+                .fold(f32::INFINITY, f32::min),
+            point_estimate: geomean(measurements.iter().map(|meas| meas.point_estimate)),
+            upper_bound: measurements
+                .iter()
+                .map(|meas| meas.upper_bound)
+                .fold(f32::NEG_INFINITY, f32::max),
+            standard_error: geomean(measurements.iter().map(|meas| meas.standard_error)),
+            mean_median_ratio: geomean(measurements.iter().map(|meas| meas.mean_median_ratio)),
+            time_ns: geomean(measurements.iter().map(|meas| meas.time_ns)),
+        }
+    }
+
+    /// Geometric mean of several measurements, including their bounds
+    fn geomean(measurements: &[Self]) -> Self {
+        assert!(!measurements.is_empty(), "cannot average zero measurements");
+        Self {
+            lower_bound: geomean(measurements.iter().map(|meas| meas.lower_bound)),
+            point_estimate: geomean(measurements.iter().map(|meas| meas.point_estimate)),
+            upper_bound: geomean(measurements.iter().map(|meas| meas.upper_bound)),
+            standard_error: geomean(measurements.iter().map(|meas| meas.standard_error)),
+            mean_median_ratio: geomean(measurements.iter().map(|meas| meas.mean_median_ratio)),
+            time_ns: geomean(measurements.iter().map(|meas| meas.time_ns)),
+        }
+    }
+
+    /// Interpolate between this measurement (`t = 0`) and another one
+    /// (`t = 1`), linearly in log space
+    fn log_interpolate(self, other: Self, t: f32) -> Self {
+        let interpolate = |value0: f32, value1: f32| value0.powf(1.0 - t) * value1.powf(t);
+        Self {
+            lower_bound: interpolate(self.lower_bound, other.lower_bound),
+            point_estimate: interpolate(self.point_estimate, other.point_estimate),
+            upper_bound: interpolate(self.upper_bound, other.upper_bound),
+            standard_error: interpolate(self.standard_error, other.standard_error),
+            mean_median_ratio: interpolate(self.mean_median_ratio, other.mean_median_ratio),
+            time_ns: interpolate(self.time_ns, other.time_ns),
+        }
+    }
+
+    /// Signed difference between this measurement and a reference one
+    ///
+    /// The confidence interval of the difference is conservatively taken to be
+    /// the widest interval that is compatible with both input intervals, while
+    /// standard errors are combined as those of independent measurements.
+    fn minus(self, reference: Self) -> Self {
+        Self {
+            lower_bound: self.lower_bound - reference.upper_bound,
+            point_estimate: self.point_estimate - reference.point_estimate,
+            upper_bound: self.upper_bound - reference.lower_bound,
+            standard_error: self.standard_error.hypot(reference.standard_error),
+            mean_median_ratio: self.mean_median_ratio,
+            time_ns: self.time_ns,
+        }
+    }
+
+    /// Ratio between this measurement and a reference one
+    ///
+    /// As with [`minus()`](Self::minus), the confidence interval of the ratio
+    /// is the widest interval that is compatible with both input intervals.
+    fn divided_by(self, reference: Self) -> Self {
+        let point_estimate = self.point_estimate / reference.point_estimate;
+        let relative_error = (self.standard_error / self.point_estimate)
+            .hypot(reference.standard_error / reference.point_estimate);
+        Self {
+            lower_bound: self.lower_bound / reference.upper_bound,
+            point_estimate,
+            upper_bound: self.upper_bound / reference.lower_bound,
+            standard_error: point_estimate.abs() * relative_error,
+            mean_median_ratio: self.mean_median_ratio,
+            time_ns: self.time_ns,
         }
     }
 }
@@ -251,14 +1455,6 @@ impl TryFrom<Estimate> for MeasurementDisplay {
     type Error = anyhow::Error;
 
     fn try_from(value: Estimate) -> Result<Self> {
-        ensure!(
-            value.confidence_interval.confidence_level == 0.95,
-            "Expecting standard 95% confidence intervals from Criterion"
-        );
-        Ok(Self {
-            point_estimate: value.point_estimate,
-            lower_bound: value.confidence_interval.lower_bound,
-            upper_bound: value.confidence_interval.upper_bound,
-        })
+        Self::from_estimates(&value, &value, ConfidenceLevel::default())
     }
 }
@@ -0,0 +1,234 @@
+//! Loading of the miniature Criterion output trees in `tests/fixtures`
+
+use clap::Parser;
+use multiplot::{criterion, criterion::ThroughputType, trace::Traces, Args};
+use std::path::PathBuf;
+
+/// Command-line arguments that load a fixture, selecting groups with `regex`
+fn fixture_args(fixture: &str, regex: &str) -> Args {
+    let input_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(fixture);
+    Args::parse_from([
+        "multiplot".as_ref(),
+        "--input-path".as_ref(),
+        input_path.as_os_str(),
+        regex.as_ref(),
+    ])
+}
+
+/// Build traces from a fixture, returning the full error chain on failure
+fn fixture_traces(fixture: &str, regex: &str) -> Result<Traces, String> {
+    multiplot::load_traces(&fixture_args(fixture, regex)).map_err(|e| format!("{e:#}"))
+}
+
+/// Check that an error chain contains the expected message
+fn assert_error_contains(result: Result<Traces, String>, expected: &str) {
+    match result {
+        Ok(traces) => panic!("expected an error, got {traces:?}"),
+        Err(error) => assert!(
+            error.contains(expected),
+            "expected error to contain {expected:?}, got {error:?}"
+        ),
+    }
+}
+
+#[test]
+fn valid_benchmarks_are_all_read() {
+    let data = criterion::read_all(&fixture_args("valid", "")).unwrap();
+    assert_eq!(data.len(), 4);
+    let mut ids = data
+        .iter()
+        .map(|info| format!("{}/{}", info.benchmark.group_id, info.benchmark.value_str))
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(
+        ids,
+        ["sum/f32/16", "sum/f32/256", "sum/f64/16", "sum/f64/256"]
+    );
+}
+
+#[test]
+fn valid_benchmarks_become_traces() {
+    let traces = fixture_traces("valid", "").unwrap();
+    assert_eq!(traces.throughput, Some(ThroughputType::Elements));
+    assert_eq!(traces.num_benchmarks, 4);
+    assert_eq!(traces.num_points(), 4);
+
+    // Directory names like sum_f32 must map back to group names like sum/f32
+    let names = traces
+        .per_trace_data
+        .iter()
+        .map(|trace| &*trace.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["sum/f32", "sum/f64"]);
+
+    // Timings of n and 2n nanoseconds for n elements are 1 and 0.5 Gelem/s
+    for (trace, expected) in traces.per_trace_data.iter().zip([1e9, 0.5e9]) {
+        let sizes = trace.data.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+        assert_eq!(sizes, [16, 256]);
+        for (_, meas) in trace.data.iter() {
+            let relative_error = (meas.point_estimate - expected).abs() / expected;
+            assert!(relative_error < 1e-6, "unexpected throughput {meas:?}");
+            assert!(meas.lower_bound < meas.point_estimate);
+            assert!(meas.upper_bound > meas.point_estimate);
+        }
+    }
+}
+
+#[test]
+fn regex_selects_benchmark_groups() {
+    let traces = fixture_traces("valid", "f64").unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(&*traces.per_trace_data[0].name, "sum/f64");
+    assert_error_contains(
+        fixture_traces("valid", "nonexistent"),
+        "specified regex does not select any trace",
+    );
+}
+
+#[test]
+fn missing_estimates_are_reported() {
+    assert_error_contains(
+        fixture_traces("missing-estimates", ""),
+        "Did not get all expected data for one benchmark",
+    );
+}
+
+#[test]
+fn mismatched_throughputs_are_reported() {
+    assert_error_contains(
+        fixture_traces("mismatched-throughput", ""),
+        "expected all traces to use throughput type",
+    );
+
+    // Each group is fine on its own
+    assert!(fixture_traces("mismatched-throughput", "copy").is_ok());
+    assert!(fixture_traces("mismatched-throughput", "sum").is_ok());
+}
+
+#[test]
+fn binary_and_decimal_bytes_share_a_plot() {
+    let traces = fixture_traces("mixed-bytes", "").unwrap();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces.throughput, Some(ThroughputType::BytesDecimal));
+}
+
+#[test]
+fn non_numeric_values_are_reported() {
+    assert_error_contains(
+        fixture_traces("non-numeric-value", ""),
+        "expected a usize criterion benchmark ID",
+    );
+}
+
+#[test]
+fn directory_names_are_not_guessed_when_recorded() {
+    // Guessing from the directory name would yield a my/bench group
+    let traces = fixture_traces("underscore-name", "^my_bench$").unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(&*traces.per_trace_data[0].name, "my_bench");
+}
+
+#[test]
+fn moved_directories_are_reported() {
+    assert_error_contains(
+        fixture_traces("moved-directory", ""),
+        "does not match the directory name sum_f32/16 recorded by Criterion",
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn non_unicode_directory_names_are_reported() {
+    use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt};
+
+    // Copy a benchmark of the valid fixture to a group directory whose name
+    // is not valid UTF-8, without recording the directory name
+    let valid_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/valid/target/criterion/sum_f32/16/new");
+    let input_path =
+        std::env::temp_dir().join(format!("multiplot-non-utf8-{}", std::process::id()));
+    let data_dir = input_path
+        .join("target/criterion")
+        .join(OsStr::from_bytes(b"sum_f\xff32"))
+        .join("16/new");
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::copy(
+        valid_dir.join("estimates.json"),
+        data_dir.join("estimates.json"),
+    )
+    .unwrap();
+    let benchmark = fs::read_to_string(valid_dir.join("benchmark.json"))
+        .unwrap()
+        .replace(r#""directory_name": "sum_f32/16", "#, "");
+    fs::write(data_dir.join("benchmark.json"), benchmark).unwrap();
+
+    // Loading these benchmarks should fail cleanly instead of panicking
+    let args = Args::parse_from([
+        "multiplot".as_ref(),
+        "--input-path".as_ref(),
+        input_path.as_os_str(),
+        "".as_ref(),
+    ]);
+    let result = multiplot::load_traces(&args).map_err(|e| format!("{e:#}"));
+    fs::remove_dir_all(&input_path).unwrap();
+    assert_error_contains(result, "should be valid Unicode");
+}
+
+#[test]
+fn functions_of_a_group_become_separate_traces() {
+    // Both functions are measured at the same problem size, which is only
+    // an error within a single trace
+    let traces = fixture_traces("functions", "").unwrap();
+    assert_eq!(traces.num_points(), 2);
+    let names = traces
+        .per_trace_data
+        .iter()
+        .map(|trace| &*trace.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["sum/naive", "sum/simd"]);
+    for trace in traces.per_trace_data.iter() {
+        let sizes = trace.data.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+        assert_eq!(sizes, [16]);
+    }
+}
+
+#[test]
+fn percentile_intervals_are_computed_from_samples() {
+    let input_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/valid");
+    let args = |regex: &str| {
+        Args::parse_from([
+            "multiplot".as_ref(),
+            "--input-path".as_ref(),
+            input_path.as_os_str(),
+            "--percentile-interval".as_ref(),
+            "10,90".as_ref(),
+            regex.as_ref(),
+        ])
+    };
+
+    // Per-iteration sample times of sum/f32 are (15, 16, 16, 17, 18) times
+    // the problem size / 16, whose 10th and 90th percentiles are 15.4 and
+    // 17.6 times that
+    let traces = multiplot::load_traces(&args("f32")).unwrap();
+    let [trace] = &traces.per_trace_data[..] else {
+        panic!("expected a single trace, got {traces:?}");
+    };
+    for (_, meas) in trace.data.iter() {
+        for (bound, expected) in [
+            (meas.lower_bound, 16.0 / 17.6),
+            (meas.upper_bound, 16.0 / 15.4),
+        ] {
+            let expected = expected * 1e9;
+            let relative_error = (bound - expected).abs() / expected;
+            assert!(relative_error < 1e-6, "unexpected bounds in {meas:?}");
+        }
+    }
+
+    // sum/f64 has no recorded samples
+    assert_error_contains(
+        multiplot::load_traces(&args("f64")).map_err(|e| format!("{e:#}")),
+        "--percentile-interval needs them",
+    );
+}
@@ -0,0 +1,224 @@
+//! Placement of asymmetric error bars and bands on the logarithmic vertical
+//! axis
+
+use clap::Parser;
+use multiplot::{
+    criterion::ThroughputType,
+    trace::{MeasurementDisplay, Metric, Trace, Traces},
+    Args,
+};
+use plotters::prelude::IntoDrawingArea;
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+};
+use std::{cell::RefCell, collections::BTreeMap, convert::Infallible, rc::Rc};
+
+/// Lines, circles and filled polygons that were drawn, with their color
+#[derive(Default)]
+struct Recording {
+    lines: Vec<(BackendCoord, BackendCoord, (u8, u8, u8))>,
+    circles: Vec<(BackendCoord, (u8, u8, u8))>,
+    polygons: Vec<(Vec<BackendCoord>, (u8, u8, u8))>,
+}
+
+/// Drawing backend that records lines, circles and filled polygons
+struct RecordingBackend(Rc<RefCell<Recording>>);
+//
+impl DrawingBackend for RecordingBackend {
+    type ErrorType = Infallible;
+
+    fn get_size(&self) -> (u32, u32) {
+        (1000, 1000)
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn draw_pixel(
+        &mut self,
+        _point: BackendCoord,
+        _color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        self.0
+            .borrow_mut()
+            .lines
+            .push((from, to, style.color().rgb));
+        Ok(())
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        _radius: u32,
+        style: &S,
+        _fill: bool,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        self.0
+            .borrow_mut()
+            .circles
+            .push((center, style.color().rgb));
+        Ok(())
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        self.0
+            .borrow_mut()
+            .polygons
+            .push((vert.into_iter().collect(), style.color().rgb));
+        Ok(())
+    }
+
+    // Text is irrelevant here, and rendering it would require system fonts
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        _text: &str,
+        _style: &TStyle,
+        _pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Infallible>> {
+        Ok(())
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        _style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Infallible>> {
+        Ok((8 * text.len() as u32, 12))
+    }
+}
+
+/// Throughput measurement with the given bounds
+fn measurement(lower_bound: f32, point_estimate: f32, upper_bound: f32) -> MeasurementDisplay {
+    MeasurementDisplay {
+        lower_bound,
+        point_estimate,
+        upper_bound,
+        standard_error: (upper_bound - lower_bound) / 4.0,
+        mean_median_ratio: 1.0,
+        time_ns: 1.0,
+    }
+}
+
+/// Traces whose error bars are asymmetric in linear space, but such that on a
+/// log scale, the upper part of the error bars is twice as long as the lower
+/// part, even though it is six times longer in linear space
+fn asymmetric_traces() -> Traces {
+    let data = [(16, 1e9), (256, 2e9), (4096, 1.5e9)]
+        .into_iter()
+        .map(|(x, y)| (x, measurement(y / 2.0, y, y * 4.0)))
+        .collect();
+    Traces {
+        throughput: Some(ThroughputType::Elements),
+        per_trace_data: vec![Trace {
+            name: "trace".into(),
+            data,
+            baseline: None,
+        }]
+        .into(),
+        num_benchmarks: 2,
+        measurement_times: None,
+        comparison: None,
+        x_labels: BTreeMap::new(),
+        confidence_level: Some(0.95),
+        metric: Metric::Measurement,
+    }
+}
+
+/// Draw traces with some extra command-line arguments
+fn draw(extra_args: &[&str], traces: Traces) -> Recording {
+    let args = Args::parse_from(
+        ["multiplot", "--output-path", "unused.svg"]
+            .iter()
+            .chain(extra_args)
+            .chain(&[""]),
+    );
+    let recording = Rc::new(RefCell::new(Recording::default()));
+    let area = RecordingBackend(recording.clone()).into_drawing_area();
+    multiplot::plot::draw_on(&args, traces, &area).unwrap();
+    drop(area);
+    Rc::into_inner(recording).unwrap().into_inner()
+}
+
+/// Check that a point lies at the right place on a vertical span
+fn assert_log_placement(center: i32, lower: i32, upper: i32) {
+    assert!(upper < center && center < lower);
+    let lower_length = f64::from(lower - center);
+    let upper_length = f64::from(center - upper);
+    let ratio = upper_length / lower_length;
+    assert!(
+        (ratio - 2.0).abs() < 0.05,
+        "expected the upper part of the uncertainty to be twice as long, got ratio {ratio}"
+    );
+}
+
+#[test]
+fn asymmetric_error_bars_are_placed_on_log_scale() {
+    // Each data point's circle is drawn at the point estimate, and should be
+    // at the right place on the vertical bar that spans the error bar
+    let recording = draw(&[], asymmetric_traces());
+    assert_eq!(recording.circles.len(), 3);
+    for &(center, color) in &recording.circles {
+        let (lower, upper) = recording
+            .lines
+            .iter()
+            .find_map(|&(from, to, line_color)| {
+                (from.0 == center.0 && to.0 == center.0 && line_color == color)
+                    .then_some((from.1.max(to.1), from.1.min(to.1)))
+            })
+            .expect("each data point should have a vertical error bar");
+        assert_log_placement(center.1, lower, upper);
+    }
+}
+
+#[test]
+fn confidence_band_encloses_the_line_on_log_scale() {
+    // There should be a single band, and no error bar
+    let recording = draw(&["--error-style", "band"], asymmetric_traces());
+    assert!(recording.circles.is_empty());
+    let [(band, band_color)] = &recording.polygons[..] else {
+        panic!(
+            "expected a single confidence band, got {:?}",
+            recording.polygons
+        );
+    };
+
+    // The band goes along the upper bounds, then back along the lower bounds
+    assert_eq!(band.len(), 6);
+    let (upper, lower) = band.split_at(3);
+    let lower = lower.iter().rev().collect::<Vec<_>>();
+
+    // At each data point, the line of the same color should go through the
+    // band, at the right place on a log scale
+    let vertices = recording
+        .lines
+        .iter()
+        .filter(|(_, _, color)| color == band_color)
+        .flat_map(|&(from, to, _)| [from, to])
+        .collect::<Vec<_>>();
+    for (upper, lower) in upper.iter().zip(lower) {
+        assert_eq!(upper.0, lower.0);
+        let vertex = vertices
+            .iter()
+            .find(|vertex| vertex.0 == upper.0)
+            .expect("the band should have a vertex at each data point");
+        assert_log_placement(vertex.1, lower.1, upper.1);
+    }
+}
@@ -0,0 +1,44 @@
+//! Reporting of errors that occur when writing plots to the filesystem
+
+use clap::Parser;
+use multiplot::Args;
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// Command-line arguments that plot the valid fixture into `output_path`
+fn output_args(output_path: &Path, extra_args: &[&str]) -> Args {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let input_path = manifest_dir.join("tests/fixtures/valid");
+    let mut args: Vec<&OsStr> = vec![
+        "multiplot".as_ref(),
+        "--input-path".as_ref(),
+        input_path.as_os_str(),
+        "--output-path".as_ref(),
+        output_path.as_os_str(),
+    ];
+    args.extend(extra_args.iter().map(OsStr::new));
+    args.push("".as_ref());
+    Args::parse_from(args)
+}
+
+#[test]
+fn unwritable_output_is_reported() {
+    // A path below a regular file cannot be created, even with superuser
+    // privileges
+    let output_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml/plot.svg");
+    let args = output_args(&output_path, &[]);
+    let traces = multiplot::load_traces(&args).unwrap();
+    let error = multiplot::plot::draw(&args, traces.clone())
+        .map_err(|e| format!("{e:#}"))
+        .expect_err("writing the plot should fail");
+    assert!(
+        error.contains("failed to write the plot"),
+        "unexpected error {error:?}"
+    );
+
+    // The escape hatch turns this error into a warning
+    let args = output_args(&output_path, &["--no-present-check"]);
+    multiplot::plot::draw(&args, traces).unwrap();
+}
@@ -0,0 +1,48 @@
+//! Text of SVG plots, which should remain selectable in SVG editors
+
+use clap::Parser;
+use multiplot::Args;
+use std::path::PathBuf;
+
+/// Contents of the `<text>` elements of an SVG plot of the valid fixture, and
+/// the font family that each of them references
+fn svg_texts(extra_args: &[&str]) -> Vec<(String, String)> {
+    let input_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/valid");
+    let args = Args::parse_from(
+        ["multiplot", "--input-path", input_path.to_str().unwrap()]
+            .iter()
+            .chain(extra_args)
+            .chain(&[""]),
+    );
+    let traces = multiplot::load_traces(&args).unwrap();
+    let mut svg = Vec::new();
+    multiplot::plot::draw_svg_to_writer(&args, traces, &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    svg.split("<text ")
+        .skip(1)
+        .map(|element| {
+            let (attributes, rest) = element.split_once('>').unwrap();
+            let (text, _) = rest.split_once("</text>").unwrap();
+            let font_family = attributes
+                .split_once("font-family=\"")
+                .and_then(|(_, family)| family.split_once('"'))
+                .map_or_else(String::new, |(family, _)| family.to_owned());
+            (text.trim().to_owned(), font_family)
+        })
+        .collect()
+}
+
+#[test]
+fn caption_and_legend_are_text_elements_in_the_chosen_font() {
+    let texts = svg_texts(&["--font", "serif", "--title", "Selectable title"]);
+    for expected in ["Selectable title", "sum/f32", "sum/f64"] {
+        assert!(
+            texts.iter().any(|(text, _)| text == expected),
+            "expected a text element containing {expected:?}, got {texts:?}"
+        );
+    }
+    assert!(
+        texts.iter().all(|(_, font_family)| font_family == "serif"),
+        "all text elements should reference the chosen font, got {texts:?}"
+    );
+}